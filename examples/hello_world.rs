@@ -13,6 +13,7 @@ use gpui_component::{
 };
 
 use gpui_style_hot_reload::my_style_data::*;
+use gpui_style_hot_reload::style_editor::StyleEditor;
 
 struct HelloWorld {
     text: SharedString,
@@ -20,6 +21,7 @@ struct HelloWorld {
     my_input_state: Entity<InputState>,
     my_progress: f32,
     sd: MyStyleData,
+    style_editor: Entity<StyleEditor<HelloWorld>>,
 }
 
 impl HelloWorld {
@@ -30,6 +32,8 @@ impl HelloWorld {
             x
         });
 
+        let host = cx.weak_entity();
+
         HelloWorld {
             text: SharedString::from(
                 "Please modify the styles in the styles.json file and save it; you'll see the window immediately apply the latest styles.",
@@ -37,7 +41,8 @@ impl HelloWorld {
             text2: SharedString::from("I've only implemented the most commonly used styles. If you'd like to support additional styles, you can simply modify the source code yourself. it's very straightforward."),
             my_input_state: my_input_state,
             my_progress: 0.0,
-            sd: init_style_data(cx, "styles.pjson".to_owned()),
+            sd: init_style_data(cx, "styles.pjson".to_owned()).observe_viewport(window, cx),
+            style_editor: cx.new(|_| StyleEditor::new(host)),
         }
     }
 }
@@ -65,7 +70,11 @@ impl Render for HelloWorld {
                 div()
                     .class("div2", sd)
                     .child(label::Label::new(self.text.clone()).class("label", sd))
-                    .child(Button::new("btn1").class("btn1", sd).label("btn1"))
+                    .child(
+                        Button::new("btn1")
+                            .interactive_class("btn1", false, sd)
+                            .label("btn1"),
+                    )
                     .child(label::Label::new(self.text2.clone()).class("label2", sd)),
             )
             .child(
@@ -74,22 +83,34 @@ impl Render for HelloWorld {
                     .child(input::Input::new(&self.my_input_state).class("textinput", sd))
                     .child(
                         Button::new("btn_simple")
-                            .class("btn_simple", sd)
+                            .interactive_class("btn_simple", false, sd)
                             .label("btn_simple"),
                     )
-                    .child(Button::new("btn2").class("btn2", sd).label("btn2"))
-                    .child(progress::Progress::new().value(self.my_progress)),
+                    .child(
+                        Button::new("btn2")
+                            .interactive_class("btn2", false, sd)
+                            .label("btn2"),
+                    )
+                    .child(progress::Progress::new().value(self.my_progress))
+                    .child(
+                        Button::new("btn_styles")
+                            .label("Styles")
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.style_editor.update(cx, |editor, cx| editor.toggle(cx))
+                            })),
+                    ),
             )
             .child(
                 div()
                     .class("div4", sd)
-                    .child(div().class("box box1", sd))
-                    .child(div().class("box box2", sd))
-                    .child(div().class("box box3", sd))
-                    .child(div().class("box box4", sd))
-                    .child(div().class("box box5", sd))
-                    .child(div().class("box box6", sd)),
+                    .child(div().interactive_class("box box1", false, sd))
+                    .child(div().interactive_class("box box2", false, sd))
+                    .child(div().interactive_class("box box3", false, sd))
+                    .child(div().interactive_class("box box4", false, sd))
+                    .child(div().interactive_class("box box5", false, sd))
+                    .child(div().interactive_class("box box6", false, sd)),
             )
+            .child(self.style_editor.clone())
     }
 }
 
@@ -35,7 +35,7 @@ struct HelloWorld {
     my_input_state: Entity<InputState>,
     my_progress: f32,
     sd: MyStyleData,
-    ld: serde_json::Value,
+    ld: LayoutData,
     /// We need to keep the subscriptions alive with the Example entity.
     ///
     /// So if the Example entity is dropped, the subscriptions are also dropped.
@@ -92,7 +92,7 @@ impl HelloWorld {
             text2: SharedString::from("Styles and layouts can be hot-loaded."),
             my_input_state: my_input_state,
             my_progress: 0.0,
-            sd: init_style_data(cx, "styles.pjson".to_owned()),
+            sd: init_style_data(cx, "styles.pjson".to_owned()).observe_viewport(window, cx),
             ld: init_layout_data(cx, "layout.pjson".to_owned()),
             _subscriptions, // Registered events must not be dropped, so store them in the global Entity.
         }
@@ -114,7 +114,7 @@ impl HelloWorld {
                     .child(input::Input::new(&this.my_input_state).class("textinput", sd))
                     .child(
                         Button::new("btn_simple_b")
-                            .class("btn_simple", sd)
+                            .interactive_class("btn_simple", false, sd)
                             .label("btn_simple")
                             .on_click(Self::btn_simple_click2(cx)),
                     )
@@ -264,7 +264,7 @@ impl SetMyStyleData for HelloWorld {
 }
 
 impl SetMyLayoutData for HelloWorld {
-    fn set_layout_data(&mut self, data: serde_json::Value) {
+    fn set_layout_data(&mut self, data: LayoutData) {
         self.ld = data;
     }
 }
@@ -277,7 +277,7 @@ impl Render for HelloWorld {
     ) -> impl IntoElement {
         //let sd = &self.sd;
 
-        add_div_by_json(&self.ld.clone(), self, cx)
+        add_layout(&self.ld.clone(), self, cx)
     }
 }
 
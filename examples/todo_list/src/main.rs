@@ -37,7 +37,7 @@ struct TodoList {
     max_id: u64,
 
     sd: MyStyleData,
-    ld: serde_json::Value,
+    ld: LayoutData,
 
     _subscriptions: Vec<Subscription>,
     sort_by_name: bool,
@@ -60,7 +60,7 @@ impl TodoList {
         let mut entity = TodoList {
             new_item_state: new_item_state,
             max_id: 0,
-            sd: init_style_data(cx, r"styles.pjson".to_owned()),
+            sd: init_style_data(cx, r"styles.pjson".to_owned()).observe_viewport(window, cx),
             ld: init_layout_data(cx, r"layout.pjson".to_owned()),
             _subscriptions,
             todo_items,
@@ -276,7 +276,7 @@ impl SetMyStyleData for TodoList {
 }
 
 impl SetMyLayoutData for TodoList {
-    fn set_layout_data(&mut self, data: serde_json::Value) {
+    fn set_layout_data(&mut self, data: LayoutData) {
         self.ld = data;
     }
 }
@@ -289,7 +289,7 @@ impl Render for TodoList {
     ) -> impl IntoElement {
         //let sd = &self.sd;
 
-        add_div_by_json(&self.ld.clone(), self, cx)
+        add_layout(&self.ld.clone(), self, cx)
     }
 }
 
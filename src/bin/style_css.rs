@@ -0,0 +1,61 @@
+// Convert between a styles file and CSS:
+//
+//   style_css export styles.pjson [styles.css]
+//   style_css import styles.css [styles.pjson]
+//
+// Without an output file the result is printed. Declarations that could not be
+// converted are listed on stderr.
+
+use anyhow::{bail, Context as _, Result};
+use std::path::Path;
+
+use gpui_style_hot_reload::my_style_data::parse_styles;
+use gpui_style_hot_reload::style_css::{
+    css_to_style_map, style_map_to_json, style_sheet_to_css, Unsupported,
+};
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (command, input, output) = match args.as_slice() {
+        [command, input] => (command.as_str(), input, None),
+        [command, input, output] => (command.as_str(), input, Some(output)),
+        _ => bail!(
+            "usage: style_css export <styles.pjson> [out.css]\n       style_css import <styles.css> [out.pjson]"
+        ),
+    };
+
+    let content = std::fs::read(input).with_context(|| format!("Failed to read `{}`", input))?;
+    let (result, report) = match command {
+        "export" => {
+            let style_data =
+                parse_styles(&content).with_context(|| format!("Failed to parse `{}`", input))?;
+            style_sheet_to_css(&style_data.style_map, &style_data.selectors)
+        }
+        "import" => {
+            let css = String::from_utf8_lossy(&content);
+            let (styles, report) =
+                css_to_style_map(&css).with_context(|| format!("Failed to parse `{}`", input))?;
+            let json = serde_json::to_string_pretty(&style_map_to_json(&styles))?;
+            (json + "\n", report)
+        }
+        other => bail!("unknown command `{}`, expected `export` or `import`", other),
+    };
+
+    match output {
+        Some(output) => std::fs::write(Path::new(output), result)
+            .with_context(|| format!("Failed to write `{}`", output))?,
+        None => print!("{}", result),
+    }
+    print_report(&report);
+    Ok(())
+}
+
+fn print_report(report: &[Unsupported]) {
+    if report.is_empty() {
+        return;
+    }
+    eprintln!("{} declaration(s) not converted:", report.len());
+    for unsupported in report {
+        eprintln!("  {}", unsupported);
+    }
+}
@@ -0,0 +1,52 @@
+// Check a styles file against the layout files using it:
+//
+//   style_lint [--allow-unused] styles.pjson layout.pjson...
+//
+// Lists undefined, unused and duplicate classes, conflicting properties and
+// invalid values, and exits with 1 if there are any, e.g. for a pre-commit
+// hook. `--allow-unused` skips unused classes, for styles also used from Rust
+// code.
+
+use anyhow::{bail, Context as _, Result};
+use std::process::ExitCode;
+
+use gpui_style_hot_reload::style_lint::{lint, LintKind};
+
+fn main() -> Result<ExitCode> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let allow_unused = match args.iter().position(|arg| arg == "--allow-unused") {
+        Some(i) => {
+            args.remove(i);
+            true
+        }
+        None => false,
+    };
+    let [styles, layouts @ ..] = args.as_slice() else {
+        bail!("usage: style_lint [--allow-unused] <styles.pjson> <layout.pjson>...");
+    };
+
+    let read = |path: &String| {
+        std::fs::read(path)
+            .map(|content| String::from_utf8_lossy(&content).to_string())
+            .with_context(|| format!("Failed to read `{}`", path))
+    };
+    let styles = (styles.as_str(), read(styles)?);
+    let layouts = layouts
+        .iter()
+        .map(|path| Ok((path.as_str(), read(path)?)))
+        .collect::<Result<Vec<_>>>()?;
+    let layouts: Vec<(&str, &str)> = layouts.iter().map(|(p, c)| (*p, c.as_str())).collect();
+
+    let issues: Vec<_> = lint((styles.0, &styles.1), &layouts)
+        .into_iter()
+        .filter(|issue| !(allow_unused && issue.kind == LintKind::UnusedClass))
+        .collect();
+    for issue in &issues {
+        println!("{}", issue);
+    }
+    if issues.is_empty() {
+        return Ok(ExitCode::SUCCESS);
+    }
+    eprintln!("{} problem(s) found", issues.len());
+    Ok(ExitCode::FAILURE)
+}
@@ -0,0 +1,26 @@
+// Write the JSON Schemas of styles and layout files:
+//
+//   style_schema [dir]
+//
+// `dir` defaults to `schemas`, where the crate ships them.
+
+use anyhow::{Context as _, Result};
+use std::path::PathBuf;
+
+use gpui_style_hot_reload::style_schema::{layout_schema, styles_schema};
+
+fn main() -> Result<()> {
+    let dir = PathBuf::from(std::env::args().nth(1).unwrap_or("schemas".to_string()));
+    std::fs::create_dir_all(&dir)?;
+
+    for (file, schema) in [
+        ("styles.schema.json", styles_schema()),
+        ("layout.schema.json", layout_schema()),
+    ] {
+        let path = dir.join(file);
+        std::fs::write(&path, serde_json::to_string_pretty(&schema)? + "\n")
+            .with_context(|| format!("Failed to write `{}`", path.display()))?;
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,372 @@
+use gpui::{hsla, Rgba};
+
+// Parse a CSS color:
+// * `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`
+// * `rgb(255, 0, 0)`, `rgba(255, 0, 0, 0.5)`, `rgb(100% 0% 0% / 50%)`
+// * `hsl(210, 50%, 40%)`, `hsla(210deg 50% 40% / 0.5)`
+// * the CSS named colors, e.g. `red`, `rebeccapurple`, `transparent`
+//
+// Any of them may be followed by an alpha override, e.g. `"#3366ff / 0.5"` or `"red / 50%"`.
+pub fn parse_css_color(s: &str) -> Result<Rgba, String> {
+    let s = s.trim();
+
+    if let Some((color, alpha)) = split_alpha_override(s) {
+        let mut color = parse_css_color(color)?;
+        color.a = parse_alpha(alpha).ok_or_else(|| format!("invalid alpha `{}`", alpha))?;
+        return Ok(color);
+    }
+
+    let lower = s.to_ascii_lowercase();
+    let parsed = if let Some(hex) = lower.strip_prefix('#') {
+        parse_hex(hex)
+    } else if let Some(args) = function_args(&lower, &["rgb", "rgba"]) {
+        parse_rgb_args(args)
+    } else if let Some(args) = function_args(&lower, &["hsl", "hsla"]) {
+        parse_hsl_args(args)
+    } else {
+        named_color(&lower)
+    };
+
+    parsed.ok_or_else(|| {
+        format!(
+            "invalid color `{}`, expected #rgb, #rrggbb, #rrggbbaa, rgb(), rgba(), hsl(), hsla() or a CSS color name",
+            s
+        )
+    })
+}
+
+// `#rrggbb`, or `#rrggbbaa` when translucent.
+pub fn hex_color(rgba: Rgba) -> String {
+    let byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let mut hex = format!(
+        "#{:02x}{:02x}{:02x}",
+        byte(rgba.r),
+        byte(rgba.g),
+        byte(rgba.b)
+    );
+    if byte(rgba.a) != 255 {
+        hex.push_str(&format!("{:02x}", byte(rgba.a)));
+    }
+    hex
+}
+
+// Finds a `/` outside of parentheses, e.g. `rgb(0 0 0) / 0.5`.
+fn split_alpha_override(s: &str) -> Option<(&str, &str)> {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            '/' if depth == 0 => return Some((s[..i].trim(), s[i + 1..].trim())),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn function_args<'a>(s: &'a str, names: &[&str]) -> Option<&'a str> {
+    let (name, rest) = s.split_once('(')?;
+    if !names.contains(&name.trim()) {
+        return None;
+    }
+    rest.trim_end().strip_suffix(')')
+}
+
+// Splits `1, 2, 3, 0.5` or `1 2 3 / 0.5` into the color components and the optional alpha.
+fn split_args(args: &str) -> Option<(Vec<&str>, Option<&str>)> {
+    let (components, alpha) = match args.split_once('/') {
+        Some((components, alpha)) => (components, Some(alpha.trim())),
+        None => (args, None),
+    };
+
+    let mut parts: Vec<&str> = if components.contains(',') {
+        components.split(',').map(str::trim).collect()
+    } else {
+        components.split_whitespace().collect()
+    };
+
+    match (parts.len(), alpha) {
+        (3, _) => Some((parts, alpha)),
+        (4, None) => {
+            let alpha = parts.pop();
+            Some((parts, alpha))
+        }
+        _ => None,
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Rgba> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok();
+    let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+
+    let [r, g, b, a] = match hex.len() {
+        3 | 4 => {
+            let a = if hex.len() == 4 { digit(3)? * 17 } else { 255 };
+            [digit(0)? * 17, digit(1)? * 17, digit(2)? * 17, a]
+        }
+        6 | 8 => {
+            let a = if hex.len() == 8 { byte(6)? } else { 255 };
+            [byte(0)?, byte(2)?, byte(4)?, a]
+        }
+        _ => return None,
+    };
+
+    Some(Rgba {
+        r: r as f32 / 255.0,
+        g: g as f32 / 255.0,
+        b: b as f32 / 255.0,
+        a: a as f32 / 255.0,
+    })
+}
+
+fn parse_rgb_args(args: &str) -> Option<Rgba> {
+    let (parts, alpha) = split_args(args)?;
+    let channel = |s: &str| -> Option<f32> {
+        match s.strip_suffix('%') {
+            Some(p) => p.trim().parse::<f32>().ok().map(|p| p / 100.0),
+            None => s.parse::<f32>().ok().map(|v| v / 255.0),
+        }
+        .map(|v| v.clamp(0.0, 1.0))
+    };
+
+    Some(Rgba {
+        r: channel(parts[0])?,
+        g: channel(parts[1])?,
+        b: channel(parts[2])?,
+        a: match alpha {
+            Some(alpha) => parse_alpha(alpha)?,
+            None => 1.0,
+        },
+    })
+}
+
+fn parse_hsl_args(args: &str) -> Option<Rgba> {
+    let (parts, alpha) = split_args(args)?;
+    let hue = parts[0].strip_suffix("deg").unwrap_or(parts[0]);
+    let hue = hue.trim().parse::<f32>().ok()?.rem_euclid(360.0) / 360.0;
+    let percent = |s: &str| -> Option<f32> {
+        let v = s
+            .strip_suffix('%')
+            .unwrap_or(s)
+            .trim()
+            .parse::<f32>()
+            .ok()?;
+        Some((v / 100.0).clamp(0.0, 1.0))
+    };
+    let alpha = match alpha {
+        Some(alpha) => parse_alpha(alpha)?,
+        None => 1.0,
+    };
+
+    Some(Rgba::from(hsla(
+        hue,
+        percent(parts[1])?,
+        percent(parts[2])?,
+        alpha,
+    )))
+}
+
+// `0.5` or `50%`
+fn parse_alpha(s: &str) -> Option<f32> {
+    let alpha = match s.strip_suffix('%') {
+        Some(p) => p.trim().parse::<f32>().ok()? / 100.0,
+        None => s.parse::<f32>().ok()?,
+    };
+    Some(alpha.clamp(0.0, 1.0))
+}
+
+fn named_color(name: &str) -> Option<Rgba> {
+    if name == "transparent" {
+        return Some(Rgba {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 0.0,
+        });
+    }
+
+    NAMED_COLORS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, hex)| gpui::rgb(*hex))
+}
+
+const NAMED_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::parse_css_color;
+    use gpui::{rgb, rgba};
+
+    #[test]
+    fn test_parse_css_color() {
+        assert_eq!(parse_css_color("#ff0000"), Ok(rgb(0xff0000)));
+        assert_eq!(parse_css_color("#F00"), Ok(rgb(0xff0000)));
+        assert_eq!(parse_css_color("#ff000080"), Ok(rgba(0xff000080)));
+        assert_eq!(parse_css_color("rgb(255, 0, 0)"), Ok(rgb(0xff0000)));
+        assert_eq!(parse_css_color("rgba(0 0 255 / 0)"), Ok(rgba(0x0000ff00)));
+        assert_eq!(parse_css_color("hsl(120, 100%, 50%)"), Ok(rgb(0x00ff00)));
+        assert_eq!(parse_css_color("rebeccapurple"), Ok(rgb(0x663399)));
+        assert_eq!(parse_css_color("red / 0.5").map(|c| c.a), Ok(0.5));
+        assert_eq!(
+            parse_css_color("hsla(0deg 100% 50% / 0.5) / 25%").map(|c| c.a),
+            Ok(0.25)
+        );
+        assert!(parse_css_color("#ff00").is_ok());
+        assert!(parse_css_color("#ff00f").is_err());
+        assert!(parse_css_color("reddish").is_err());
+        assert!(parse_css_color("rgb(1, 2)").is_err());
+    }
+}
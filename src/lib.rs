@@ -1,5 +1,14 @@
+pub mod css_color;
 pub mod my_context_ext;
 pub mod my_layout_data;
 pub mod my_style_data;
 pub mod my_text_input_ext;
+pub mod pjson_edit;
 pub mod pjson_to_rust_helper;
+pub mod style_css;
+pub mod style_editor;
+pub mod style_lint;
+pub mod style_schema;
+pub mod style_selector;
+pub mod style_transition;
+pub mod style_utility;
@@ -5,7 +5,9 @@ use gpui_component::{
     input::{self, InputState},
     label,
     scroll::ScrollableElement,
+    Disableable,
 };
+use std::sync::Arc;
 use std::{any::Any, path::PathBuf, thread};
 
 use alanthinker_dynamic_get_field_trait::{call, DynamicGetter};
@@ -13,22 +15,318 @@ use gpui::*;
 
 use crate::{
     my_context_ext::MyContextExt,
-    my_style_data::{SetMyStyleData, StylableElement},
+    my_style_data::{
+        ElementStates, MyStyleData, ResolvedStyle, SetMyStyleData, StylableElement, StyleKey,
+    },
+    style_selector::SelectorTarget,
 };
 
-pub fn load_layout(path: &PathBuf) -> anyhow::Result<serde_json::Value> {
+pub fn load_layout(path: &PathBuf) -> anyhow::Result<LayoutData> {
     let content = std::fs::read(path.as_path())?;
     let json = pjson::PJsonReader::from_pjson(&content);
     let json = String::from_utf8_lossy(&json).to_string();
     let json_value: serde_json::Value = serde_json::from_str(&json)?;
-    Ok(json_value)
+    Ok(LayoutData::new(json_value))
+}
+
+// A loaded layout: its JSON, which derefs to it, and beside it the style cache
+// key of every element, see `StyleKeys`. Cheap to clone.
+#[derive(Debug, Clone)]
+pub struct LayoutData {
+    json: Arc<serde_json::Value>,
+    style_keys: Arc<StyleKeys>,
+}
+
+impl LayoutData {
+    pub fn new(json: serde_json::Value) -> Self {
+        report_attributes(&json, "/");
+        let style_keys = StyleKeys::new(&json, &[], "div");
+        Self {
+            json: Arc::new(json),
+            style_keys: Arc::new(style_keys),
+        }
+    }
+}
+
+impl std::ops::Deref for LayoutData {
+    type Target = serde_json::Value;
+
+    fn deref(&self) -> &serde_json::Value {
+        &self.json
+    }
+}
+
+// The key for `MyStyleData::resolve_element_keyed` of an element and, by index,
+// of its `children`. It covers what its style depends on: its own and its
+// ancestors' type, id and class, and its inline style. Computed once per load,
+// so that rendering a cached element neither matches selectors nor hashes its
+// inline style.
+#[derive(Debug, Default)]
+struct StyleKeys {
+    key: Option<StyleKey>,
+    // Where the element is in the layout, e.g. `/0/2/` for the third child of the
+    // first child of the root `/`. Transitions of elements without an id are
+    // tracked by it.
+    location: String,
+    children: Vec<StyleKeys>,
+}
+
+impl StyleKeys {
+    fn new(value: &serde_json::Value, ancestors: &[SelectorTarget], element: &str) -> Self {
+        Self::at(value, ancestors, element, "/".to_string())
+    }
+
+    fn at(
+        value: &serde_json::Value,
+        ancestors: &[SelectorTarget],
+        element: &str,
+        location: String,
+    ) -> Self {
+        let path = selector_path(ancestors, element, value);
+        let children = match value.get("children") {
+            Some(serde_json::Value::Array(children)) => children
+                .iter()
+                .enumerate()
+                .map(|(index, child)| {
+                    let etype = child.get("type").and_then(|t| t.as_str());
+                    match etype.and_then(selector_element) {
+                        Some(element) => {
+                            let location = format!("{}{}/", location, index);
+                            StyleKeys::at(child, &path, element, location)
+                        }
+                        None => StyleKeys::default(),
+                    }
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        Self {
+            key: Some(StyleKey::new(&path, value.get("style"))),
+            location,
+            children,
+        }
+    }
+
+    fn child(keys: Option<&StyleKeys>, index: usize) -> Option<&StyleKeys> {
+        keys.and_then(|keys| keys.children.get(index))
+    }
+}
+
+// The element name selectors match a layout element `type` by.
+fn selector_element(etype: &str) -> Option<&'static str> {
+    match etype {
+        "div" => Some("div"),
+        "label" => Some("label"),
+        "text_input" | "input" => Some("input"),
+        "button" => Some("button"),
+        _ => None,
+    }
+}
+
+// What a layout attribute holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeValue {
+    String,
+    Bool,
+    // An inline style rule.
+    Style,
+    // Elements, or strings shown as labels.
+    Children,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LayoutAttribute {
+    pub name: &'static str,
+    pub value: AttributeValue,
+    pub required: bool,
+    pub description: &'static str,
+}
+
+const fn attribute(
+    name: &'static str,
+    value: AttributeValue,
+    description: &'static str,
+) -> LayoutAttribute {
+    LayoutAttribute {
+        name,
+        value,
+        required: false,
+        description,
+    }
+}
+
+const fn required(
+    name: &'static str,
+    value: AttributeValue,
+    description: &'static str,
+) -> LayoutAttribute {
+    LayoutAttribute {
+        required: true,
+        ..attribute(name, value, description)
+    }
+}
+
+// A layout element: the `type`s it is written with and the attributes besides
+// `type` that `add_*_by_json` read. Layouts are checked against them when
+// loaded, and `style_schema::layout_schema` describes them.
+#[derive(Debug)]
+pub struct LayoutElement {
+    pub types: &'static [&'static str],
+    pub attributes: &'static [LayoutAttribute],
+}
+
+// The attributes of every element but `fn`, see `set_attributes`.
+const STYLED: [LayoutAttribute; 3] = [
+    attribute("id", AttributeValue::String, "Matched by `#id` selectors."),
+    attribute(
+        "class",
+        AttributeValue::String,
+        "Space separated classes of the styles file.",
+    ),
+    attribute("style", AttributeValue::Style, "An inline style rule."),
+];
+
+pub const LAYOUT_ELEMENTS: &[LayoutElement] = &[
+    LayoutElement {
+        types: &["div"],
+        attributes: &[
+            STYLED[0],
+            STYLED[1],
+            STYLED[2],
+            attribute(
+                "children",
+                AttributeValue::Children,
+                "Elements, or strings shown as labels.",
+            ),
+            attribute("overflow_x_hidden", AttributeValue::Bool, ""),
+            attribute("overflow_y_hidden", AttributeValue::Bool, ""),
+            attribute("overflow_x_scrollbar", AttributeValue::Bool, ""),
+            attribute("overflow_y_scrollbar", AttributeValue::Bool, ""),
+        ],
+    },
+    LayoutElement {
+        types: &["label"],
+        attributes: &[
+            STYLED[0],
+            STYLED[1],
+            STYLED[2],
+            attribute(
+                "bind",
+                AttributeValue::String,
+                "A `SharedString` or `String` field of the view to show.",
+            ),
+            attribute(
+                "label",
+                AttributeValue::String,
+                "The text, unless `bind` is set.",
+            ),
+        ],
+    },
+    LayoutElement {
+        types: &["input", "text_input"],
+        attributes: &[
+            STYLED[0],
+            STYLED[1],
+            STYLED[2],
+            required(
+                "bind",
+                AttributeValue::String,
+                "An `Entity<InputState>` field of the view.",
+            ),
+        ],
+    },
+    LayoutElement {
+        types: &["button"],
+        attributes: &[
+            STYLED[0],
+            STYLED[1],
+            STYLED[2],
+            attribute("label", AttributeValue::String, ""),
+            attribute(
+                "on_click",
+                AttributeValue::String,
+                "A method of the view returning the click handler.",
+            ),
+            attribute(
+                "disabled",
+                AttributeValue::Bool,
+                "Disables the button and applies the `disabled` sub-rules.",
+            ),
+        ],
+    },
+    LayoutElement {
+        types: &["fn"],
+        attributes: &[required(
+            "name",
+            AttributeValue::String,
+            "A method of the view returning the element.",
+        )],
+    },
+];
+
+fn layout_element(etype: &str) -> Option<&'static LayoutElement> {
+    LAYOUT_ELEMENTS
+        .iter()
+        .find(|element| element.types.contains(&etype))
+}
+
+// Report the unknown elements and attributes of a layout, and its missing
+// required attributes, once per load.
+fn report_attributes(value: &serde_json::Value, location: &str) {
+    let Some(map) = value.as_object() else {
+        if !value.is_string() {
+            tracing::error!("Layout element {} is not an object", location);
+        }
+        return;
+    };
+    let etype = map.get("type").and_then(|t| t.as_str()).unwrap_or_default();
+    let Some(element) = layout_element(etype) else {
+        tracing::error!("Unknown layout element type `{}` at {}", etype, location);
+        return;
+    };
+    for name in map.keys().filter(|name| *name != "type") {
+        if !element.attributes.iter().any(|a| a.name == name) {
+            tracing::error!("Unknown attribute `{}` of {} at {}", name, etype, location);
+        }
+    }
+    for attribute in element.attributes.iter().filter(|a| a.required) {
+        if !map.contains_key(attribute.name) {
+            tracing::error!(
+                "Missing attribute `{}` of {} at {}",
+                attribute.name,
+                etype,
+                location
+            );
+        }
+    }
+    if let Some(serde_json::Value::Array(children)) = map.get("children") {
+        for (index, child) in children.iter().enumerate() {
+            report_attributes(child, &format!("{}{}/", location, index));
+        }
+    }
+}
+
+// Resolve the style of the element `value` at `path`, by the key computed when
+// the layout was loaded. Layouts built in code have none.
+fn resolve_element<R>(
+    sd: &MyStyleData,
+    value: &serde_json::Value,
+    path: &[SelectorTarget],
+    keys: Option<&StyleKeys>,
+    f: impl FnOnce(&ResolvedStyle) -> R,
+) -> R {
+    let inline = value.get("style");
+    match keys.and_then(|keys| keys.key.as_ref()) {
+        Some(key) => sd.resolve_element_keyed(key, path, inline, f),
+        None => sd.resolve_element(path, inline, f),
+    }
 }
 
 pub trait SetMyLayoutData {
-    fn set_layout_data(&mut self, data: serde_json::Value);
+    fn set_layout_data(&mut self, data: LayoutData);
 }
 
-pub fn init_layout_data<T: 'static>(cx: &mut Context<T>, layout_path: String) -> serde_json::Value
+pub fn init_layout_data<T: 'static>(cx: &mut Context<T>, layout_path: String) -> LayoutData
 where
     T: SetMyLayoutData,
 {
@@ -41,7 +339,7 @@ where
 
     let style_path2 = style_path.clone();
 
-    let layout_data: serde_json::Value = match load_layout(&PathBuf::from(style_path)) {
+    let layout_data = match load_layout(&PathBuf::from(style_path)) {
         Ok(data) => {
             tracing::info!("✅ Layout reloaded successfully.");
             data
@@ -62,7 +360,7 @@ fn watch_layout_data<T: 'static>(cx: &mut Context<T>, layout_path: String)
 where
     T: SetMyLayoutData,
 {
-    let (th_sender, mut th_receiver) = futures::channel::mpsc::channel::<serde_json::Value>(100); // std::sync::mpsc::channel::<serde_json::Value>();
+    let (th_sender, mut th_receiver) = futures::channel::mpsc::channel::<LayoutData>(100); // std::sync::mpsc::channel::<LayoutData>();
 
     let be = cx.background_executor().clone();
 
@@ -100,7 +398,7 @@ where
 fn run_watcher(
     path: PathBuf,
     be: BackgroundExecutor,
-    mut sender: futures::channel::mpsc::Sender<serde_json::Value>,
+    mut sender: futures::channel::mpsc::Sender<LayoutData>,
 ) -> Result<()> {
     use notify::{recommended_watcher, RecursiveMode, Watcher};
 
@@ -136,13 +434,59 @@ fn run_watcher(
 }
 
 pub fn add_div_by_json<E>(value: &serde_json::Value, e: &mut E, cx: &mut Context<E>) -> AnyElement
+where
+    E: DynamicGetter + SetMyStyleData + Any + 'static,
+{
+    add_div_in(value, &[], None, e, cx)
+}
+
+// Same as `add_div_by_json` for a whole layout, using its precomputed style keys.
+pub fn add_layout<E>(layout: &LayoutData, e: &mut E, cx: &mut Context<E>) -> AnyElement
+where
+    E: DynamicGetter + SetMyStyleData + Any + 'static,
+{
+    add_div_in(&layout.json, &[], Some(&layout.style_keys), e, cx)
+}
+
+// `ancestors` are the elements containing this one, matched by descendant selectors.
+// `keys` are the element's style keys when it is part of a loaded layout.
+fn add_div_in<E>(
+    value: &serde_json::Value,
+    ancestors: &[SelectorTarget],
+    keys: Option<&StyleKeys>,
+    e: &mut E,
+    cx: &mut Context<E>,
+) -> AnyElement
 where
     E: DynamicGetter + SetMyStyleData + Any + 'static,
 {
     let mut ele = div();
 
-    ele = set_attributes(ele, value, e);
-    ele = set_children(ele, value, e, cx);
+    let path = selector_path(ancestors, "div", value);
+    // A div animates the `transition` properties of its style, see
+    // `StylableElement::transition_states`. Its previous style is looked up by
+    // its id or, in a loaded layout, by its location.
+    let scrollbar = ["overflow_x_scrollbar", "overflow_y_scrollbar"]
+        .iter()
+        .any(|attribute| value.get(attribute) == Some(&serde_json::Value::Bool(true)));
+    let transition_id = {
+        let sd = e.get_style_data();
+        let animated = resolve_element(sd, value, &path, keys, |resolved| {
+            resolved.rule.transition.is_some()
+        });
+        let id = path.last().and_then(|target| target.id).map(str::to_string);
+        id.or_else(|| keys.map(|keys| keys.location.clone()))
+            .filter(|_| animated && !scrollbar)
+    };
+    // With a transition, the state sub-rules are applied by `transition_states`.
+    let (states, apply): (_, fn(Div, &ResolvedStyle) -> Div) = match transition_id {
+        Some(_) => (ElementStates::Stateful, apply_plain),
+        None => (ElementStates::Interactive, |ele, resolved| {
+            apply_interactive(ele, resolved, false)
+        }),
+    };
+    ele = set_attributes(ele, value, &path, keys, e, states, apply);
+    ele = set_children(ele, value, &path, keys, e, cx);
 
     let mut overflow_x_scrollbar = false;
     let mut overflow_y_scrollbar = false;
@@ -178,6 +522,12 @@ where
             panic!("This should never happen.")
         }
         ele2.into_any_element()
+    } else if let Some(id) = transition_id {
+        let sd = e.get_style_data();
+        let ele = ele.id(ElementId::Name(id.clone().into()));
+        resolve_element(sd, value, &path, keys, |resolved| {
+            ele.transition_states(&id, resolved, false, sd)
+        })
     } else {
         ele.into_any_element()
     }
@@ -187,64 +537,89 @@ pub fn add_button_by_json<E>(value: &serde_json::Value, e: &mut E, cx: &Context<
 where
     E: DynamicGetter + SetMyStyleData + Any + 'static,
 {
+    add_button_in(value, &[], None, e, cx)
+}
+
+fn add_button_in<E>(
+    value: &serde_json::Value,
+    ancestors: &[SelectorTarget],
+    keys: Option<&StyleKeys>,
+    e: &mut E,
+    cx: &Context<E>,
+) -> AnyElement
+where
+    E: DynamicGetter + SetMyStyleData + Any + 'static,
+{
+    let path = selector_path(ancestors, "button", value);
+    // Without an id, a button is told apart by its location in the layout.
+    let id = match path.last().and_then(|target| target.id) {
+        Some(id) => id.to_string(),
+        None => keys.map(|keys| keys.location.clone()).unwrap_or_default(),
+    };
+    let mut ele = button::Button::new(ElementId::Name(id.into()));
+    let mut disabled = false;
     match value {
         serde_json::Value::Object(map) => {
-            match &map.get("id").unwrap_or_default() {
-                serde_json::Value::String(id) => {
-                    let id = ElementId::Name(id.into());
-                    let mut ele = button::Button::new(id);
-
-                    match &map.get("label").unwrap_or_default() {
-                        serde_json::Value::String(label) => {
-                            ele = ele.label(label);
-                        }
-                        _ => {
-                            tracing::error!("button's 'label' attribute must be set");
-                        }
-                    }
+            match &map.get("label").unwrap_or_default() {
+                serde_json::Value::String(label) => {
+                    ele = ele.label(label);
+                }
+                _ => {
+                    tracing::error!("button's 'label' attribute must be set");
+                }
+            }
 
-                    match &map.get("on_click").unwrap_or_default() {
-                        serde_json::Value::String(on_click) => {
-                            let view = cx.entity().downgrade();
-                            let r = call::call_and_downcast::<
-                                E,
-                                Box<dyn Fn(&ClickEvent, &mut Window, &mut App) + 'static>,
-                            >(on_click, e, &[&view]);
-                            match r {
-                                Ok(handler) => {
-                                    ele = ele.on_click(handler);
-                                }
-                                Err(e) => {
-                                    tracing::error!("Failed call_and_downcast, on_click: {:?}", e);
-                                }
-                            }
+            match &map.get("on_click").unwrap_or_default() {
+                serde_json::Value::String(on_click) => {
+                    let view = cx.entity().downgrade();
+                    let r = call::call_and_downcast::<
+                        E,
+                        Box<dyn Fn(&ClickEvent, &mut Window, &mut App) + 'static>,
+                    >(on_click, e, &[&view]);
+                    match r {
+                        Ok(handler) => {
+                            ele = ele.on_click(handler);
                         }
-                        _ => {
-                            // User did not set on_click event
+                        Err(e) => {
+                            tracing::error!("Failed call_and_downcast, on_click: {:?}", e);
                         }
                     }
-
-                    ele = set_attributes(ele, value, e);
-
-                    return ele.into_any_element();
                 }
                 _ => {
-                    tracing::error!("button's 'id' attribute must be set");
+                    // User did not set on_click event
                 }
             }
+
+            if let Some(serde_json::Value::Bool(true)) = map.get("disabled") {
+                ele = ele.disabled(true);
+                disabled = true;
+            }
         }
         _ => {
             tracing::error!("Failed to get json Object type.");
         }
     }
 
-    let ele = button::Button::new("");
-    let ele = set_attributes(ele, value, e);
+    let states = ElementStates::Interactive;
+    let apply = move |ele, resolved: &ResolvedStyle| apply_interactive(ele, resolved, disabled);
+    ele = set_attributes(ele, value, &path, keys, e, states, apply);
 
     ele.into_any_element()
 }
 
 pub fn add_label_by_json<E>(value: &serde_json::Value, e: &E) -> AnyElement
+where
+    E: DynamicGetter + SetMyStyleData + Any + 'static,
+{
+    add_label_in(value, &[], None, e)
+}
+
+fn add_label_in<E>(
+    value: &serde_json::Value,
+    ancestors: &[SelectorTarget],
+    keys: Option<&StyleKeys>,
+    e: &E,
+) -> AnyElement
 where
     E: DynamicGetter + SetMyStyleData + Any + 'static,
 {
@@ -293,7 +668,16 @@ where
 
     match ele {
         Some(mut ele) => {
-            ele = set_attributes(ele, value, e);
+            let path = selector_path(ancestors, "label", value);
+            ele = set_attributes(
+                ele,
+                value,
+                &path,
+                keys,
+                e,
+                ElementStates::Plain,
+                apply_plain,
+            );
             ele.into_any_element()
         }
         None => {
@@ -304,6 +688,18 @@ where
 }
 
 pub fn add_text_input_by_json<E>(value: &serde_json::Value, e: &E) -> AnyElement
+where
+    E: DynamicGetter + SetMyStyleData + Any + 'static,
+{
+    add_text_input_in(value, &[], None, e)
+}
+
+fn add_text_input_in<E>(
+    value: &serde_json::Value,
+    ancestors: &[SelectorTarget],
+    keys: Option<&StyleKeys>,
+    e: &E,
+) -> AnyElement
 where
     E: DynamicGetter + SetMyStyleData + Any + 'static,
 {
@@ -335,7 +731,16 @@ where
 
     match ele {
         Some(mut ele) => {
-            ele = set_attributes(ele, value, e);
+            let path = selector_path(ancestors, "input", value);
+            ele = set_attributes(
+                ele,
+                value,
+                &path,
+                keys,
+                e,
+                ElementStates::Plain,
+                apply_plain,
+            );
             ele.into_any_element()
         }
         None => {
@@ -384,39 +789,50 @@ where
     }
 }
 
-fn set_attributes<T, E>(mut ele: T, value: &serde_json::Value, e: &E) -> T
+// `ancestors` plus the element described by `value`.
+fn selector_path<'a>(
+    ancestors: &[SelectorTarget<'a>],
+    element: &'a str,
+    value: &'a serde_json::Value,
+) -> Vec<SelectorTarget<'a>> {
+    let mut path = ancestors.to_vec();
+    path.push(SelectorTarget {
+        element,
+        id: value.get("id").and_then(|id| id.as_str()),
+        classes: value.get("class").and_then(|c| c.as_str()).unwrap_or(""),
+    });
+    path
+}
+
+// `path` is the element itself preceded by its ancestors, see `selector_path`.
+// `apply` applies the resolved style to the element, which has the gpui
+// `states`; the state sub-rules it has no refinement for are reported.
+fn set_attributes<T, E>(
+    mut ele: T,
+    value: &serde_json::Value,
+    path: &[SelectorTarget],
+    keys: Option<&StyleKeys>,
+    e: &E,
+    states: ElementStates,
+    apply: impl FnOnce(T, &ResolvedStyle) -> T,
+) -> T
 where
-    T: Styled,
+    T: StylableElement,
     E: DynamicGetter + SetMyStyleData + Any + 'static,
 {
     match value {
-        serde_json::Value::Object(map) => {
-            match &map.get("class").unwrap_or_default() {
-                serde_json::Value::String(classes) => {
-                    let sd = e.get_style_data();
-                    ele = ele.class(classes, &sd);
-                }
-                _ => {
-                    // class attribute not set
-                }
-            }
-
-            match map.get("style") {
-                Some(styles) => {
-                    let style_rule = serde_json::from_value(styles.clone());
-                    match style_rule {
-                        Ok(style_rule) => {
-                            ele = ele.apply_style_rule(&style_rule);
-                        }
-                        Err(e) => {
-                            tracing::error!("wrong style: {}", e);
-                        }
-                    }
+        serde_json::Value::Object(_) => {
+            let sd = e.get_style_data();
+
+            // Selectors, classes and the inline style are resolved together
+            // (and cached until the next reload), so that their hover/active/focus
+            // sub-rules end up in a single refinement.
+            ele = resolve_element(sd, value, path, keys, |resolved| {
+                if let Some(target) = path.last() {
+                    sd.report_ignored_states(target.element, target.classes, resolved, states);
                 }
-                None => {
-                    // style attribute not set
-                }
-            }
+                apply(ele, resolved)
+            });
         }
         _ => {
             tracing::error!("Failed to get json Object type.");
@@ -426,7 +842,26 @@ where
     ele
 }
 
-fn set_children<T, E>(mut ele: T, value: &serde_json::Value, e: &mut E, cx: &mut Context<E>) -> T
+fn apply_plain<T: StylableElement>(ele: T, resolved: &ResolvedStyle) -> T {
+    ele.apply_resolved_style(resolved, false)
+}
+
+fn apply_interactive<T>(ele: T, resolved: &ResolvedStyle, disabled: bool) -> T
+where
+    T: StylableElement + InteractiveElement,
+{
+    ele.apply_resolved_style(resolved, disabled)
+        .apply_interactive_states(resolved, disabled)
+}
+
+fn set_children<T, E>(
+    mut ele: T,
+    value: &serde_json::Value,
+    path: &[SelectorTarget],
+    keys: Option<&StyleKeys>,
+    e: &mut E,
+    cx: &mut Context<E>,
+) -> T
 where
     E: DynamicGetter + SetMyStyleData + Any + 'static,
     T: Styled + ParentElement,
@@ -434,7 +869,8 @@ where
     match value {
         serde_json::Value::Object(map) => match &map.get("children").unwrap_or_default() {
             serde_json::Value::Array(children) => {
-                for child in children {
+                for (index, child) in children.iter().enumerate() {
+                    let keys = StyleKeys::child(keys, index);
                     match child {
                         serde_json::Value::Object(map) => {
                             let etype = map
@@ -444,12 +880,14 @@ where
                                 .unwrap_or_default();
 
                             match etype {
-                                "div" => ele = ele.child(add_div_by_json(child, e, cx)),
-                                "label" => ele = ele.child(add_label_by_json(child, e)),
+                                "div" => ele = ele.child(add_div_in(child, path, keys, e, cx)),
+                                "label" => ele = ele.child(add_label_in(child, path, keys, e)),
                                 "text_input" | "input" => {
-                                    ele = ele.child(add_text_input_by_json(child, e))
+                                    ele = ele.child(add_text_input_in(child, path, keys, e))
+                                }
+                                "button" => {
+                                    ele = ele.child(add_button_in(child, path, keys, e, cx))
                                 }
-                                "button" => ele = ele.child(add_button_by_json(child, e, cx)),
                                 "fn" => ele = ele.child(add_fn_by_json(child, e, cx)),
                                 _ => {
                                     tracing::error!("Unknown element: {}", etype);
@@ -1,48 +1,85 @@
 use anyhow::{Context as _, Result};
 use futures::{SinkExt, StreamExt};
 use gpui::{prelude::*, *};
-use serde::Deserialize;
+use gpui_component::Theme;
+use serde::{Deserialize, Serialize, Serializer};
 
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::any::Any;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::thread;
 
+use crate::css_color::{hex_color, parse_css_color};
 use crate::my_context_ext::MyContextExt;
+use crate::style_selector::{Selector, SelectorRule, SelectorTarget, Specificity};
+use crate::style_transition::{RunningTransition, StyleTransition, Transitions};
+use crate::style_utility::parse_utility_class;
 
 // === Style system definition ===
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct StyleRule {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub size_full: Option<bool>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub bg_color: Option<String>,
+    pub bg_color: Option<StyleColor>,
+    // A solid color or a gradient; replaces `bg_color`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<StyleBackground>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_color: Option<StyleColor>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub text_color: Option<String>,
+    pub font_size: Option<StyleFontSize>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub font_size: Option<String>,
+    pub font_weight: Option<StyleFontWeight>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub font_weight: Option<String>,
+    pub font_family: Option<String>,
+    // Numbers are pixels like other lengths; use e.g. `"150%"` for a multiple
+    // of the font size.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_height: Option<StyleLength>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_align: Option<StyleTextAlign>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub italic: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub underline: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_through: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub whitespace: Option<StyleWhitespace>,
+    // Truncate overflowing text with an ellipsis (…).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_ellipsis: Option<bool>,
+    // The number of lines to show before truncating.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_clamp: Option<usize>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub display: Option<String>,
+    pub display: Option<StyleDisplay>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub justify_content: Option<String>,
+    pub justify_content: Option<StyleJustifyContent>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub align_items: Option<String>,
+    pub align_items: Option<StyleAlignItems>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub align_self: Option<String>,
+    pub align_self: Option<StyleAlignItems>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub flex_direction: Option<String>,
+    pub flex_direction: Option<StyleFlexDirection>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub flex_grow: Option<f32>,
@@ -51,473 +88,3752 @@ pub struct StyleRule {
     pub flex_shrink: Option<f32>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub flex_basis: Option<f32>,
+    pub flex_basis: Option<StyleLength>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub width: Option<f32>,
+    pub width: Option<StyleLength>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub height: Option<f32>,
+    pub height: Option<StyleLength>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub min_width: Option<f32>,
+    pub min_width: Option<StyleLength>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub min_height: Option<f32>,
+    pub min_height: Option<StyleLength>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub max_width: Option<f32>,
+    pub max_width: Option<StyleLength>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub max_height: Option<f32>,
+    pub max_height: Option<StyleLength>,
 
-    #[serde(skip_serializing_if = "Option::is_name")]
-    pub shadow: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<StylePosition>,
+    // Offsets of a positioned element; `inset` sets all four.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inset: Option<StyleLength>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top: Option<StyleLength>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub right: Option<StyleLength>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bottom: Option<StyleLength>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub left: Option<StyleLength>,
 
-    #[serde(skip_serializing_if = "Option::is_name")]
-    pub border_width: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opacity: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visible: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<StyleCursor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overflow_x: Option<StyleOverflow>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overflow_y: Option<StyleOverflow>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub border_color: Option<String>,
+    pub shadow: Option<StyleShadow>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub border_style: Option<String>,
+    pub border_width: Option<f32>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub rounded: Option<String>,
+    pub border_color: Option<StyleColor>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub margin: Option<f32>,
+    pub border_style: Option<StyleBorderStyle>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub margin_top: Option<f32>,
+    pub border_top: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub margin_right: Option<f32>,
+    pub border_right: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub margin_bottom: Option<f32>,
+    pub border_bottom: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub margin_left: Option<f32>,
+    pub border_left: Option<f32>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub padding: Option<f32>,
+    pub rounded: Option<StyleRadius>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rounded_tl: Option<StyleRadius>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rounded_tr: Option<StyleRadius>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rounded_bl: Option<StyleRadius>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rounded_br: Option<StyleRadius>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub padding_top: Option<f32>,
+    pub margin: Option<StyleLength>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub padding_right: Option<f32>,
+    pub margin_top: Option<StyleLength>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub padding_bottom: Option<f32>,
+    pub margin_right: Option<StyleLength>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub padding_left: Option<f32>,
+    pub margin_bottom: Option<StyleLength>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub gap: Option<f32>,
-}
+    pub margin_left: Option<StyleLength>,
 
-#[derive(Debug)]
-pub struct MyStyleData {
-    pub style_map: StyleMap,
-}
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub padding: Option<StyleLength>,
 
-pub type StyleMap = HashMap<String, StyleRule>;
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub padding_top: Option<StyleLength>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub padding_right: Option<StyleLength>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub padding_bottom: Option<StyleLength>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub padding_left: Option<StyleLength>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gap: Option<StyleLength>,
+    // Column and row gaps, overriding `gap`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gap_x: Option<StyleLength>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gap_y: Option<StyleLength>,
 
-pub fn load_styles(path: &PathBuf) -> anyhow::Result<MyStyleData> {
-    let content = std::fs::read(path.as_path())?;
-    let json = pjson::PJsonReader::from_pjson(&content);
-    let json = String::from_utf8_lossy(&json).to_string();
-    let styles: StyleMap = serde_json::from_str(&json)?;
+    // Grid container: the number of equally sized columns and rows.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grid_cols: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grid_rows: Option<u16>,
+    // Grid item: a span is a number of tracks or `"full"`; start and end are
+    // 1-based lines, negative ones count from the end.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub col_span: Option<GridSpan>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row_span: Option<GridSpan>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub col_start: Option<i16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub col_end: Option<i16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row_start: Option<i16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row_end: Option<i16>,
 
-    Ok(MyStyleData { style_map: styles })
-}
+    // Animate changes of some properties, see `StyleTransition`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transition: Option<StyleTransition>,
 
-// Utility function: parse color
-fn parse_color(hex: &str) -> Rgba {
-    let r = Rgba::try_from(hex);
-    match r {
-        Ok(r) => r,
-        Err(e) => {
-            tracing::error!("parse_color {} err:{:?}", hex, e);
-            Rgba::default()
-        }
-    }
-}
+    // Pseudo-state sub-rules, e.g. `hover: { bg_color: "#eeeeee" }`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hover: Option<Box<StyleRule>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<Box<StyleRule>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focus: Option<Box<StyleRule>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disabled: Option<Box<StyleRule>>,
 
-fn parse_font_size(size: &str) -> Pixels {
-    match size {
-        "sm" => px(12.0),
-        "base" => px(16.0),
-        "lg" => px(20.0),
-        "xl" => px(24.0),
-        "2xl" => px(32.0),
-        _ => {
-            let x = size.parse::<f32>();
-            match x {
-                Ok(x) => px(x),
-                Err(e) => {
-                    tracing::error!("parse_font_size {} err: {:?}", size, e);
-                    px(16.0)
-                }
-            }
-        }
-    }
+    // Sub-rules applied when the window is at least as wide as the breakpoint,
+    // e.g. `breakpoints: { md: { flex_direction: "row" } }`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breakpoints: Option<BTreeMap<Breakpoint, Box<StyleRule>>>,
+
+    // Rules of this class from top-level `@media(...)` blocks and `themes`
+    // overlays, in file order.
+    #[serde(skip)]
+    pub media: Vec<(MediaQuery, StyleRule)>,
 }
 
-pub trait StylableElement: Sized + Styled {
-    fn class(self, classes: impl Into<String>, style_data: &MyStyleData) -> Self {
-        let rules = &style_data.style_map;
-        let classes: String = classes.into();
-        let class_vec: Vec<_> = classes.split(' ').collect();
-        let mut self2 = self;
-        for class in class_vec {
-            if let Some(rule) = rules.get(class) {
-                self2 = self2.apply_style_rule(rule);
+// Copies every property that is set in `other` over `self`.
+macro_rules! merge_properties {
+    ($target:ident, $other:ident, $($field:ident),* $(,)?) => {
+        $(
+            if $other.$field.is_some() {
+                $target.$field = $other.$field.clone();
             }
-        }
-
-        self2
-    }
-
-    fn apply_style_rule_json(self, value: serde_json::value::Value) -> Self;
-    fn apply_style_rule(self, rule: &StyleRule) -> Self;
+        )*
+    };
 }
 
-impl<T> StylableElement for T
-where
-    T: Styled,
-{
-    fn apply_style_rule_json(mut self, value: serde_json::value::Value) -> Self {
-        self = self.apply_style_rule(&serde_json::from_value(value).unwrap());
-        self
+impl StyleRule {
+    // The rule as written in a styles file. Unlike `serde_json::to_value`, which
+    // widens `f32`s to e.g. `0.10000000149011612`, numbers keep their short form.
+    pub fn to_json(&self) -> serde_json::Value {
+        let json = serde_json::to_string(self).expect("a style rule always serializes");
+        serde_json::from_str(&json).expect("serialized json is valid")
     }
 
-    fn apply_style_rule(mut self, rule: &StyleRule) -> Self {
-        if let Some(size_full) = rule.size_full {
-            if size_full {
-                self = self.size_full();
-            }
-        }
-        if let Some(bg) = &rule.bg_color {
-            self = self.bg(parse_color(bg));
+    // Cascade `other` on top of `self`, property by property, as a later class
+    // overrides an earlier one. Shorthands (`margin`, `padding`, `inset`,
+    // `border_width`, `rounded`, `gap`, grid spans) reset the per-side values
+    // set before them.
+    pub fn merge(&mut self, other: &StyleRule) {
+        if other.margin.is_some() {
+            self.margin_top = None;
+            self.margin_right = None;
+            self.margin_bottom = None;
+            self.margin_left = None;
         }
-        if let Some(c) = &rule.text_color {
-            self = self.text_color(parse_color(c));
+        if other.padding.is_some() {
+            self.padding_top = None;
+            self.padding_right = None;
+            self.padding_bottom = None;
+            self.padding_left = None;
         }
-        if let Some(fs) = &rule.font_size {
-            self = self.text_size(parse_font_size(fs));
-        }
-        if let Some(font_weight) = &rule.font_weight {
-            self = self.font_weight(gpui::FontWeight::from(
-                font_weight.parse::<f32>().unwrap_or(0.0),
-            ));
+        if other.border_width.is_some() {
+            self.border_top = None;
+            self.border_right = None;
+            self.border_bottom = None;
+            self.border_left = None;
         }
-        if let Some(display) = &rule.display {
-            self = match display.as_str() {
-                "block" => self.block(),
-                "flex" => self.flex(),
-                "grid" => self.grid(),
-                "none" => {
-                    self.style().display = Some(Display::None);
-                    self
-                }
-                _ => self,
-            };
+        if other.rounded.is_some() {
+            self.rounded_tl = None;
+            self.rounded_tr = None;
+            self.rounded_bl = None;
+            self.rounded_br = None;
         }
-        if let Some(jc) = &rule.justify_content {
-            self = match jc.as_str() {
-                "center" => self.justify_center(),
-                "flex-start" => {
-                    self.style().justify_content = Some(JustifyContent::FlexStart);
-                    self
-                }
-                "start" => self.justify_start(),
-                "flex-end" => {
-                    self.style().justify_content = Some(JustifyContent::FlexEnd);
-                    self
-                }
-                "end" => self.justify_end(),
-                "space-between" => self.justify_between(),
-                "space-around" => self.justify_around(),
-                "space-evenly" => {
-                    self.style().justify_content = Some(JustifyContent::SpaceEvenly);
-                    self
-                }
-                "stretch" => {
-                    self.style().justify_content = Some(JustifyContent::Stretch);
-                    self
-                }
-                _ => self,
-            };
+        // `bg_color` and `background` set the same thing.
+        if other.bg_color.is_some() {
+            self.background = None;
         }
-        if let Some(ai) = &rule.align_items {
-            self = match ai.as_str() {
-                "center" => self.items_center(),
-                "flex-start" => self.items_start(),
-                "start" => self.items_start(),
-                "flex-end" => self.items_end(),
-                "end" => self.items_end(),
-                "baseline" => self.items_baseline(),
-                "stretch" => {
-                    self.style().align_items = Some(AlignItems::Stretch);
-                    self
-                }
-                _ => self,
-            };
+        if other.background.is_some() {
+            self.bg_color = None;
         }
-        if let Some(ai) = &rule.align_self {
-            self = match ai.as_str() {
-                "center" => {
-                    self.style().align_self = Some(AlignSelf::Center);
-                    self
-                }
-                "flex-start" => {
-                    self.style().align_self = Some(AlignSelf::FlexStart);
-                    self
-                }
-                "start" => {
-                    self.style().align_self = Some(AlignSelf::Start);
-                    self
-                }
-                "flex-end" => {
-                    self.style().align_self = Some(AlignSelf::FlexEnd);
-                    self
-                }
-                "end" => {
-                    self.style().align_self = Some(AlignSelf::End);
-                    self
-                }
-                "baseline" => {
-                    self.style().align_self = Some(AlignSelf::Baseline);
-                    self
-                }
-                "stretch" => {
-                    self.style().align_self = Some(AlignSelf::Stretch);
-                    self
-                }
-                _ => self,
-            };
+        if other.inset.is_some() {
+            self.top = None;
+            self.right = None;
+            self.bottom = None;
+            self.left = None;
         }
-        if let Some(fd) = &rule.flex_direction {
-            self = match fd.as_str() {
-                "row" => self.flex_row(),
-                "column" => self.flex_col(),
-                _ => self,
-            };
+        if other.gap.is_some() {
+            self.gap_x = None;
+            self.gap_y = None;
         }
-        if let Some(flex_grow) = rule.flex_grow {
-            self.style().flex_grow = Some(flex_grow);
+        if other.col_span.is_some() {
+            self.col_start = None;
+            self.col_end = None;
         }
-        if let Some(flex_shrink) = rule.flex_shrink {
-            self.style().flex_shrink = Some(flex_shrink);
+        if other.row_span.is_some() {
+            self.row_start = None;
+            self.row_end = None;
         }
 
-        if let Some(flex_basis) = rule.flex_basis {
-            self.style().flex_basis = Some(Length::Definite(DefiniteLength::Absolute(
-                AbsoluteLength::Pixels(px(flex_basis)),
-            )));
-        }
+        merge_properties!(
+            self,
+            other,
+            size_full,
+            bg_color,
+            background,
+            text_color,
+            font_size,
+            font_weight,
+            font_family,
+            line_height,
+            text_align,
+            italic,
+            underline,
+            line_through,
+            whitespace,
+            text_ellipsis,
+            line_clamp,
+            display,
+            justify_content,
+            align_items,
+            align_self,
+            flex_direction,
+            flex_grow,
+            flex_shrink,
+            flex_basis,
+            width,
+            height,
+            min_width,
+            min_height,
+            max_width,
+            max_height,
+            position,
+            inset,
+            top,
+            right,
+            bottom,
+            left,
+            opacity,
+            visible,
+            cursor,
+            overflow_x,
+            overflow_y,
+            shadow,
+            border_width,
+            border_color,
+            border_style,
+            border_top,
+            border_right,
+            border_bottom,
+            border_left,
+            rounded,
+            rounded_tl,
+            rounded_tr,
+            rounded_bl,
+            rounded_br,
+            margin,
+            margin_top,
+            margin_right,
+            margin_bottom,
+            margin_left,
+            padding,
+            padding_top,
+            padding_right,
+            padding_bottom,
+            padding_left,
+            gap,
+            gap_x,
+            gap_y,
+            grid_cols,
+            grid_rows,
+            col_span,
+            row_span,
+            col_start,
+            col_end,
+            row_start,
+            row_end,
+            transition,
+        );
+
+        merge_state_rule(&mut self.hover, &other.hover);
+        merge_state_rule(&mut self.active, &other.active);
+        merge_state_rule(&mut self.focus, &other.focus);
+        merge_state_rule(&mut self.disabled, &other.disabled);
 
-        if let Some(w) = rule.width {
-            self = self.w(px(w));
+        if let Some(breakpoints) = &other.breakpoints {
+            let target = self.breakpoints.get_or_insert_with(Default::default);
+            for (breakpoint, rule) in breakpoints {
+                target.entry(*breakpoint).or_default().merge(rule);
+            }
         }
-        if let Some(h) = rule.height {
-            self = self.h(px(h));
+        self.media.extend(other.media.iter().cloned());
+    }
+
+    // Merge the breakpoint, `@media` and theme sub-rules matching `state` into the rule.
+    pub fn for_media(mut self, state: &MediaState) -> StyleRule {
+        let breakpoints = self.breakpoints.take();
+        let media = std::mem::take(&mut self.media);
+
+        for (breakpoint, rule) in breakpoints.into_iter().flatten() {
+            if breakpoint.query().matches(state) {
+                self.merge(&rule);
+            }
         }
-        if let Some(min_width) = rule.min_width {
-            self = self.min_w(px(min_width))
+        for (query, rule) in media {
+            if query.matches(state) {
+                self.merge(&rule);
+            }
         }
-        if let Some(min_height) = rule.min_height {
-            self = self.min_h(px(min_height))
+        self
+    }
+
+    // Replace `"theme.*"` colors, including those of the state sub-rules, by
+    // their value in `palette`.
+    pub fn with_theme_colors(mut self, palette: &ThemePalette) -> StyleRule {
+        for color in [
+            &mut self.bg_color,
+            &mut self.text_color,
+            &mut self.border_color,
+        ] {
+            *color = color.take().and_then(|c| c.with_theme_colors(palette));
         }
-        if let Some(max_width) = rule.max_width {
-            self = self.max_w(px(max_width))
+        if let Some(background) = self.background.take() {
+            self.background = background.with_theme_colors(palette);
         }
-        if let Some(max_height) = rule.max_height {
-            self = self.max_h(px(max_height))
+        if let Some(StyleShadow::Custom(shadows)) = &mut self.shadow {
+            shadows.retain_mut(|shadow| match shadow.color.take() {
+                Some(color) => match color.with_theme_colors(palette) {
+                    Some(color) => {
+                        shadow.color = Some(color);
+                        true
+                    }
+                    None => false,
+                },
+                None => true,
+            });
         }
-
-        if let Some(shadow) = &rule.shadow {
-            match shadow.as_str() {
-                "2xs" => self = self.shadow_2xs(),
-                "xs" => self = self.shadow_xs(),
-                "sm" => self = self.shadow_sm(),
-                "md" => self = self.shadow_md(),
-                "lg" => self = self.rounded_lg(),
-                "xl" => self = self.shadow_xl(),
-                "2xl" => self = self.shadow_2xl(),
-                "none" => self = self.shadow_none(),
-                _ => {}
+        for state in [
+            &mut self.hover,
+            &mut self.active,
+            &mut self.focus,
+            &mut self.disabled,
+        ] {
+            if let Some(rule) = state.take() {
+                *state = Some(Box::new(rule.with_theme_colors(palette)));
             }
         }
-        if let Some(bw) = rule.border_width {
-            self = self.border(px(bw));
+        self
+    }
+
+    // Merge the given rules, in order, into a single rule.
+    pub fn merge_all<'a>(rules: impl IntoIterator<Item = &'a StyleRule>) -> StyleRule {
+        let mut merged = StyleRule::default();
+        for rule in rules {
+            merged.merge(rule);
         }
-        if let Some(bc) = &rule.border_color {
-            self = self.border_color(parse_color(bc));
+        merged
+    }
+}
+
+fn merge_state_rule(target: &mut Option<Box<StyleRule>>, other: &Option<Box<StyleRule>>) {
+    if let Some(other) = other {
+        target.get_or_insert_with(Default::default).merge(other);
+    }
+}
+
+// A color in a `StyleRule`, see `parse_css_color` for the accepted syntax,
+// or a color of the active `gpui_component::Theme` such as `"theme.primary"`.
+// Invalid colors are reported when the styles are loaded.
+#[derive(Debug, Clone)]
+pub enum StyleColor {
+    Rgba(Rgba),
+    // A color in another notation than hex, e.g. `"red"` or `"hsl(210, 50%, 40%)"`,
+    // with its source so it is written back the way it was written.
+    Css(Rgba, SharedString),
+    Theme(&'static str),
+}
+
+// By value, so `"red"` equals `"#ff0000"`.
+impl PartialEq for StyleColor {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (StyleColor::Theme(a), StyleColor::Theme(b)) => a == b,
+            (StyleColor::Theme(_), _) | (_, StyleColor::Theme(_)) => false,
+            (a, b) => a.value() == b.value(),
         }
-        if let Some(bs) = &rule.border_style {
-            if bs == "dashed" {
-                self = self.border_dashed();
+    }
+}
+
+impl TryFrom<String> for StyleColor {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, String> {
+        match value.trim().strip_prefix("theme.") {
+            Some(name) => THEME_COLOR_NAMES
+                .iter()
+                .find(|n| **n == name)
+                .map(|n| StyleColor::Theme(n))
+                .ok_or_else(|| {
+                    format!(
+                        "unknown theme color `{}`, expected one of {}",
+                        name,
+                        THEME_COLOR_NAMES.join(", ")
+                    )
+                }),
+            None => {
+                let rgba = parse_css_color(&value)?;
+                let source = value.trim();
+                Ok(if source.starts_with('#') && !source.contains('/') {
+                    StyleColor::Rgba(rgba)
+                } else {
+                    StyleColor::Css(rgba, source.to_string().into())
+                })
             }
         }
-        if let Some(g) = rule.gap {
-            self = self.gap(px(g));
+    }
+}
+
+// Not derived: serde would require `'de: 'static` for the `&'static str`.
+impl<'de> Deserialize<'de> for StyleColor {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        StyleColor::try_from(value).map_err(serde::de::Error::custom)
+    }
+}
+
+// Written back as `#rrggbb` (`#rrggbbaa` when translucent), as the source of
+// other notations or as `theme.<name>`.
+impl Serialize for StyleColor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            StyleColor::Rgba(rgba) => serializer.serialize_str(&hex_color(*rgba)),
+            StyleColor::Css(_, source) => serializer.serialize_str(source),
+            StyleColor::Theme(name) => serializer.serialize_str(&format!("theme.{}", name)),
         }
-        if let Some(rounded) = &rule.rounded {
-            match rounded.as_str() {
-                "md" => self = self.rounded_md(),
-                "lg" => self = self.rounded_lg(),
-                "full" => self = self.rounded_full(),
-                _ => {}
+    }
+}
+
+impl StyleColor {
+    // The color to paint with. Theme colors are only known once resolved
+    // against a `ThemePalette`, see `StyleRule::with_theme_colors`.
+    pub fn rgba(&self) -> Option<Rgba> {
+        match *self {
+            StyleColor::Rgba(rgba) | StyleColor::Css(rgba, _) => Some(rgba),
+            StyleColor::Theme(name) => {
+                // Only once per color, as this runs on every render.
+                static WARNED: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+                let mut warned = WARNED.lock().unwrap_or_else(|e| e.into_inner());
+                if !warned.contains(&name) {
+                    warned.push(name);
+                    tracing::warn!("Theme color `theme.{}` used without a theme", name);
+                }
+                None
             }
         }
+    }
 
-        // Set margin
-        let mut margin = Edges {
-            top: rule.margin.map(px).unwrap_or(px(0.0)),
-            right: rule.margin.map(px).unwrap_or(px(0.0)),
-            bottom: rule.margin.map(px).unwrap_or(px(0.0)),
-            left: rule.margin.map(px).unwrap_or(px(0.0)),
-        };
-        margin = Edges {
-            top: rule.margin_top.map(px).unwrap_or(margin.top),
-            right: rule.margin_right.map(px).unwrap_or(margin.right),
-            bottom: rule.margin_bottom.map(px).unwrap_or(margin.bottom),
-            left: rule.margin_left.map(px).unwrap_or(margin.left),
-        };
-        self = self
-            .m_0()
-            .mt(margin.top)
-            .mr(margin.right)
-            .mb(margin.bottom)
-            .ml(margin.left);
-
-        // Set padding
-        let mut padding = Edges {
-            top: rule.padding.map(px).unwrap_or(px(0.0)),
-            right: rule.padding.map(px).unwrap_or(px(0.0)),
-            bottom: rule.padding.map(px).unwrap_or(px(0.0)),
-            left: rule.padding.map(px).unwrap_or(px(0.0)),
-        };
-        padding = Edges {
-            top: rule.padding_top.map(px).unwrap_or(padding.top),
-            right: rule.padding_right.map(px).unwrap_or(padding.right),
-            bottom: rule.padding_bottom.map(px).unwrap_or(padding.bottom),
-            left: rule.padding_left.map(px).unwrap_or(padding.left),
-        };
-        self = self
-            .p_0()
-            .pt(padding.top)
-            .pr(padding.right)
-            .pb(padding.bottom)
-            .pl(padding.left);
+    // The color when it does not depend on a theme.
+    pub fn value(&self) -> Option<Rgba> {
+        match *self {
+            StyleColor::Rgba(rgba) | StyleColor::Css(rgba, _) => Some(rgba),
+            StyleColor::Theme(_) => None,
+        }
+    }
 
-        self
+    fn with_theme_colors(self, palette: &ThemePalette) -> Option<StyleColor> {
+        match self {
+            StyleColor::Rgba(_) | StyleColor::Css(..) => Some(self),
+            StyleColor::Theme(name) => match palette.get(name) {
+                Some(color) => Some(StyleColor::Rgba((*color).into())),
+                None => {
+                    tracing::warn!("Theme color `theme.{}` is not available", name);
+                    None
+                }
+            },
+        }
     }
 }
 
-pub fn init_style_data<T: 'static>(cx: &mut Context<T>, style_path: String) -> MyStyleData
-where
-    T: SetMyStyleData,
-{
-    // If the style_path file doesn't exist, replace it with ../../{style_path}
-    let style_path = if std::path::Path::new(&style_path).exists() {
-        style_path
-    } else {
-        format!("../../{}", style_path)
-    };
+// The colors of the active `gpui_component::Theme` by name.
+pub type ThemePalette = HashMap<&'static str, Hsla>;
 
-    let style_path2 = style_path.clone();
+macro_rules! theme_colors {
+    ($($name:ident),* $(,)?) => {
+        // The `ThemeColor` fields usable as `"theme.<name>"`.
+        pub const THEME_COLOR_NAMES: &[&str] = &[$(stringify!($name)),*];
 
-    let style_data: MyStyleData = match load_styles(&PathBuf::from(style_path)) {
-        Ok(data) => {
-            tracing::info!("✅ Styles reloaded successfully.");
-            data
-        }
-        Err(e) => {
-            tracing::error!("{:?}", e);
-            thread::sleep(std::time::Duration::from_secs(1));
-            panic!("Failed to load styles.")
+        fn theme_palette(theme: &Theme) -> ThemePalette {
+            [$((stringify!($name), theme.$name)),*].into_iter().collect()
         }
     };
+}
 
-    watch_style_data(cx, style_path2);
+theme_colors!(
+    background,
+    foreground,
+    border,
+    input,
+    ring,
+    primary,
+    primary_foreground,
+    primary_hover,
+    primary_active,
+    secondary,
+    secondary_foreground,
+    secondary_hover,
+    secondary_active,
+    muted,
+    muted_foreground,
+    accent,
+    accent_foreground,
+    danger,
+    danger_foreground,
+    success,
+    success_foreground,
+    warning,
+    warning_foreground,
+    info,
+    info_foreground,
+    link,
+    selection,
+    caret,
+    popover,
+    popover_foreground,
+    list,
+    list_hover,
+    list_active,
+    title_bar,
+    title_bar_border,
+    sidebar,
+    sidebar_foreground,
+    sidebar_border,
+);
 
-    style_data
+// A length in a `StyleRule`. Plain numbers are pixels; strings may be `"12px"`,
+// `"1.5rem"`, `"50%"`, a fraction such as `"1/3"`, `"full"` (100%) or `"auto"`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "LengthValue")]
+pub enum StyleLength {
+    Px(f32),
+    Rems(f32),
+    // Fraction of the parent's size, 1.0 is 100%.
+    Relative(f32),
+    // `"a/b"` and `"full"`, kept apart from `Relative` to be written back as such.
+    Fraction(f32, f32),
+    Full,
+    Auto,
 }
 
-pub trait SetMyStyleData {
-    fn set_style_data(&mut self, data: MyStyleData);
-    fn get_style_data(&self) -> &MyStyleData;
+impl Serialize for StyleLength {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            StyleLength::Px(n) => serializer.serialize_f32(*n),
+            StyleLength::Rems(n) => serializer.serialize_str(&format!("{}rem", n)),
+            StyleLength::Relative(n) => serializer.serialize_str(&format!("{}%", n * 100.0)),
+            StyleLength::Fraction(a, b) => serializer.serialize_str(&format!("{}/{}", a, b)),
+            StyleLength::Full => serializer.serialize_str("full"),
+            StyleLength::Auto => serializer.serialize_str("auto"),
+        }
+    }
 }
 
-fn watch_style_data<T: 'static>(cx: &mut Context<T>, style_path: String)
-where
-    T: SetMyStyleData,
-{
-    let (th_sender, mut th_receiver) = futures::channel::mpsc::channel::<MyStyleData>(100); // std::sync::mpsc::channel::<MyStyleData>();
-
-    let be = cx.background_executor().clone();
+// The `col_span`/`row_span` of a grid item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "LengthValue")]
+pub enum GridSpan {
+    Tracks(u16),
+    Full,
+}
 
-    std::thread::spawn(|| {
-        if let Err(e) = run_watcher(PathBuf::from(style_path), be, th_sender) {
-            tracing::error!("File watcher failed: {:?}", e);
+impl Serialize for GridSpan {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            GridSpan::Tracks(n) => serializer.serialize_u16(*n),
+            GridSpan::Full => serializer.serialize_str("full"),
         }
-    });
+    }
+}
 
-    cx.my_spawn(async move |entity, cx| {
-        loop {
-            match th_receiver.next().await {
-                Some(r) => {
-                    tracing::info!("Refresh window");
+impl TryFrom<LengthValue> for GridSpan {
+    type Error = String;
 
-                    entity
-                        .upgrade()
-                        .context("entity upgrade fail.")?
-                        .update(cx, |this, cx| {
-                            this.set_style_data(r);
-                            cx.notify(); // Must notify UI to update
-                        })?;
-                }
-                None => {}
+    fn try_from(value: LengthValue) -> Result<Self, Self::Error> {
+        match value {
+            LengthValue::Number(n) if n >= 1. && n.fract() == 0. && n <= u16::MAX as f32 => {
+                Ok(GridSpan::Tracks(n as u16))
             }
+            LengthValue::Text(s) if s.trim() == "full" => Ok(GridSpan::Full),
+            LengthValue::Number(n) => Err(format!(
+                "invalid span `{}`, expected a positive integer or `full`",
+                n
+            )),
+            LengthValue::Text(s) => Err(format!(
+                "invalid span `{}`, expected a positive integer or `full`",
+                s
+            )),
         }
+    }
+}
 
-        #[allow(unreachable_code)]
-        Ok(())
-    })
-    .detach();
+// A font size: pixels, `"1.25rem"`, or a size keyword, see `parse_font_size`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "LengthValue")]
+pub struct StyleFontSize(pub AbsoluteLength);
+
+impl Serialize for StyleFontSize {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            AbsoluteLength::Pixels(px) => serializer.serialize_f32(px.into()),
+            AbsoluteLength::Rems(rems) => serializer.serialize_str(&format!("{}rem", rems.0)),
+        }
+    }
 }
 
-// File watcher (hot reload)
-fn run_watcher(
-    path: PathBuf,
-    be: BackgroundExecutor,
-    mut sender: futures::channel::mpsc::Sender<MyStyleData>,
-) -> Result<()> {
-    use notify::{recommended_watcher, RecursiveMode, Watcher};
+impl TryFrom<LengthValue> for StyleFontSize {
+    type Error = String;
+
+    fn try_from(value: LengthValue) -> Result<Self, Self::Error> {
+        match value {
+            LengthValue::Number(n) => Ok(StyleFontSize(px(n).into())),
+            LengthValue::Text(s) => parse_font_size(&s).map(StyleFontSize),
+        }
+    }
+}
+
+// A font weight: a number from 100 to 900 or a name such as `"bold"`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "LengthValue")]
+pub struct StyleFontWeight(pub f32);
+
+impl Serialize for StyleFontWeight {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f32(self.0)
+    }
+}
+
+impl TryFrom<LengthValue> for StyleFontWeight {
+    type Error = String;
+
+    fn try_from(value: LengthValue) -> Result<Self, Self::Error> {
+        let weight = match value {
+            LengthValue::Number(n) => n,
+            LengthValue::Text(s) => match s.trim() {
+                "thin" => FontWeight::THIN.0,
+                "extra-light" | "extralight" => FontWeight::EXTRA_LIGHT.0,
+                "light" => FontWeight::LIGHT.0,
+                "normal" => FontWeight::NORMAL.0,
+                "medium" => FontWeight::MEDIUM.0,
+                "semibold" => FontWeight::SEMIBOLD.0,
+                "bold" => FontWeight::BOLD.0,
+                "extra-bold" | "extrabold" => FontWeight::EXTRA_BOLD.0,
+                "black" => FontWeight::BLACK.0,
+                other => other.parse().map_err(|_| {
+                    format!(
+                        "invalid font weight `{}`, expected a number or one of \
+                         `thin`, `extra-light`, `light`, `normal`, `medium`, \
+                         `semibold`, `bold`, `extra-bold`, `black`",
+                        other
+                    )
+                })?,
+            },
+        };
+        Ok(StyleFontWeight(weight))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LengthValue {
+    Number(f32),
+    Text(String),
+}
+
+impl TryFrom<LengthValue> for StyleLength {
+    type Error = String;
+
+    fn try_from(value: LengthValue) -> Result<Self, String> {
+        match value {
+            LengthValue::Number(n) => Ok(StyleLength::Px(n)),
+            LengthValue::Text(s) => s.parse(),
+        }
+    }
+}
+
+impl std::str::FromStr for StyleLength {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let number = |n: &str| {
+            n.trim().parse::<f32>().map_err(|_| {
+                format!(
+                    "invalid length `{}`, expected a number, \"12px\", \"1.5rem\", \"50%\", \"1/3\", \"full\" or \"auto\"",
+                    s
+                )
+            })
+        };
 
-    let path2 = path.clone();
-    let mut watcher =
-        recommended_watcher(move |res: Result<notify::Event, notify::Error>| match res {
-            Ok(event) => {
-                if event
-                    .paths
-                    .iter()
-                    .any(|path| path.canonicalize().ok() == path.canonicalize().ok())
-                {
-                    match load_styles(&path) {
-                        Ok(style_data) => {
-                            tracing::info!("✅ Styles reloaded successfully.");
-                            be.block(async {
-                                let _ = sender.send(style_data).await;
-                            });
-                        }
-                        Err(e) => {
-                            tracing::error!("{:?}", e);
-                        }
+        match s {
+            "auto" => Ok(StyleLength::Auto),
+            "full" => Ok(StyleLength::Full),
+            _ => {
+                if let Some(n) = s.strip_suffix('%') {
+                    Ok(StyleLength::Relative(number(n)? / 100.0))
+                } else if let Some(n) = s.strip_suffix("rem") {
+                    Ok(StyleLength::Rems(number(n)?))
+                } else if let Some(n) = s.strip_suffix("px") {
+                    Ok(StyleLength::Px(number(n)?))
+                } else if let Some((a, b)) = s.split_once('/') {
+                    let b = number(b)?;
+                    if b == 0.0 {
+                        return Err(format!("invalid length `{}`, division by zero", s));
                     }
+                    Ok(StyleLength::Fraction(number(a)?, b))
+                } else {
+                    Ok(StyleLength::Px(number(s)?))
                 }
             }
+        }
+    }
+}
+
+impl From<StyleLength> for Length {
+    fn from(length: StyleLength) -> Self {
+        // Only `auto` has no definite length.
+        length.definite().map_or(Length::Auto, Length::Definite)
+    }
+}
+
+impl StyleLength {
+    // The fraction of the parent's size of `Relative`, `Fraction` and `Full`.
+    pub fn relative(self) -> Option<f32> {
+        match self {
+            StyleLength::Relative(f) => Some(f),
+            StyleLength::Fraction(a, b) => Some(a / b),
+            StyleLength::Full => Some(1.0),
+            _ => None,
+        }
+    }
+
+    // Padding and gap have no `auto`, those get `None`.
+    pub fn definite(self) -> Option<DefiniteLength> {
+        match self {
+            StyleLength::Px(v) => Some(px(v).into()),
+            StyleLength::Rems(v) => Some(rems(v).into()),
+            StyleLength::Auto => None,
+            length => length.relative().map(relative),
+        }
+    }
+}
+
+fn definite_length(length: StyleLength, property: &str) -> Option<DefiniteLength> {
+    let definite = length.definite();
+    if definite.is_none() {
+        tracing::error!("`auto` is not supported for {}", property);
+    }
+    definite
+}
+
+// Keyword properties. Unknown values are rejected by serde with an
+// "unknown variant `x`, expected one of ..." error.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StyleDisplay {
+    Block,
+    Flex,
+    Grid,
+    None,
+}
+
+impl From<StyleDisplay> for Display {
+    fn from(value: StyleDisplay) -> Self {
+        match value {
+            StyleDisplay::Block => Display::Block,
+            StyleDisplay::Flex => Display::Flex,
+            StyleDisplay::Grid => Display::Grid,
+            StyleDisplay::None => Display::None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StyleJustifyContent {
+    Start,
+    End,
+    FlexStart,
+    FlexEnd,
+    Center,
+    Stretch,
+    SpaceBetween,
+    SpaceAround,
+    SpaceEvenly,
+}
+
+impl From<StyleJustifyContent> for JustifyContent {
+    fn from(value: StyleJustifyContent) -> Self {
+        match value {
+            StyleJustifyContent::Start => JustifyContent::Start,
+            StyleJustifyContent::End => JustifyContent::End,
+            StyleJustifyContent::FlexStart => JustifyContent::FlexStart,
+            StyleJustifyContent::FlexEnd => JustifyContent::FlexEnd,
+            StyleJustifyContent::Center => JustifyContent::Center,
+            StyleJustifyContent::Stretch => JustifyContent::Stretch,
+            StyleJustifyContent::SpaceBetween => JustifyContent::SpaceBetween,
+            StyleJustifyContent::SpaceAround => JustifyContent::SpaceAround,
+            StyleJustifyContent::SpaceEvenly => JustifyContent::SpaceEvenly,
+        }
+    }
+}
+
+// Used for both `align_items` and `align_self`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StyleAlignItems {
+    Start,
+    End,
+    FlexStart,
+    FlexEnd,
+    Center,
+    Baseline,
+    Stretch,
+}
+
+impl From<StyleAlignItems> for AlignItems {
+    fn from(value: StyleAlignItems) -> Self {
+        match value {
+            StyleAlignItems::Start => AlignItems::Start,
+            StyleAlignItems::End => AlignItems::End,
+            StyleAlignItems::FlexStart => AlignItems::FlexStart,
+            StyleAlignItems::FlexEnd => AlignItems::FlexEnd,
+            StyleAlignItems::Center => AlignItems::Center,
+            StyleAlignItems::Baseline => AlignItems::Baseline,
+            StyleAlignItems::Stretch => AlignItems::Stretch,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StyleFlexDirection {
+    Row,
+    Column,
+    RowReverse,
+    ColumnReverse,
+}
+
+impl From<StyleFlexDirection> for FlexDirection {
+    fn from(value: StyleFlexDirection) -> Self {
+        match value {
+            StyleFlexDirection::Row => FlexDirection::Row,
+            StyleFlexDirection::Column => FlexDirection::Column,
+            StyleFlexDirection::RowReverse => FlexDirection::RowReverse,
+            StyleFlexDirection::ColumnReverse => FlexDirection::ColumnReverse,
+        }
+    }
+}
+
+// `background` is a color, a linear gradient such as
+// `{ linear_gradient: { angle: 90, stops: ["#ff0000", { color: "#0000ff", position: 0.8 }] } }`,
+// or an image such as `{ image: "images/hero.png", fit: "cover" }`.
+// gpui gradients have exactly two stops; positions default to 0 and 1.
+// gpui only fills backgrounds with colors, so an image is drawn by an `img`
+// child added to divs behind the children added after it, see `BackgroundImage`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "BackgroundValue")]
+pub enum StyleBackground {
+    Solid(StyleColor),
+    LinearGradient {
+        angle: f32,
+        stops: [GradientStop; 2],
+    },
+    // Relative to the styles file, see `MyStyleData::with_source`.
+    Image {
+        path: String,
+        fit: StyleObjectFit,
+    },
+}
+
+// How a background image is scaled to the element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StyleObjectFit {
+    Fill,
+    Contain,
+    #[default]
+    Cover,
+    ScaleDown,
+    None,
+}
+
+impl From<StyleObjectFit> for ObjectFit {
+    fn from(value: StyleObjectFit) -> Self {
+        match value {
+            StyleObjectFit::Fill => ObjectFit::Fill,
+            StyleObjectFit::Contain => ObjectFit::Contain,
+            StyleObjectFit::Cover => ObjectFit::Cover,
+            StyleObjectFit::ScaleDown => ObjectFit::ScaleDown,
+            StyleObjectFit::None => ObjectFit::None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradientStop {
+    pub color: StyleColor,
+    pub position: f32,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BackgroundValue {
+    Color(String),
+    Other(serde_json::Value),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BackgroundObject {
+    linear_gradient: LinearGradientValue,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BackgroundImageValue {
+    image: String,
+    #[serde(default)]
+    fit: StyleObjectFit,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LinearGradientValue {
+    #[serde(default)]
+    angle: f32,
+    stops: Vec<GradientStopValue>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum GradientStopValue {
+    Color(StyleColor),
+    Stop { color: StyleColor, position: f32 },
+}
+
+impl TryFrom<BackgroundValue> for StyleBackground {
+    type Error = String;
+
+    fn try_from(value: BackgroundValue) -> Result<Self, Self::Error> {
+        let value = match value {
+            BackgroundValue::Color(color) => {
+                return StyleColor::try_from(color).map(StyleBackground::Solid)
+            }
+            BackgroundValue::Other(value) => value,
+        };
+        let expected = |e: serde_json::Error| {
+            format!(
+                "expected a color, `{{ linear_gradient: {{ angle, stops }} }}` \
+                 or `{{ image, fit }}`: {}",
+                e
+            )
+        };
+
+        if value.get("image").is_some() {
+            let image: BackgroundImageValue = serde_json::from_value(value).map_err(expected)?;
+            return Ok(StyleBackground::Image {
+                path: image.image,
+                fit: image.fit,
+            });
+        }
+        let object: BackgroundObject = serde_json::from_value(value).map_err(expected)?;
+        let gradient = object.linear_gradient;
+        let [from, to]: [GradientStopValue; 2] =
+            gradient.stops.try_into().map_err(|stops: Vec<_>| {
+                format!(
+                    "a linear gradient needs exactly 2 stops, found {}",
+                    stops.len()
+                )
+            })?;
+        let stop = |value: GradientStopValue, default_position: f32| match value {
+            GradientStopValue::Color(color) => GradientStop {
+                color,
+                position: default_position,
+            },
+            GradientStopValue::Stop { color, position } => GradientStop { color, position },
+        };
+
+        Ok(StyleBackground::LinearGradient {
+            angle: gradient.angle,
+            stops: [stop(from, 0.0), stop(to, 1.0)],
+        })
+    }
+}
+
+impl Serialize for StyleBackground {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            StyleBackground::Solid(color) => color.serialize(serializer),
+            StyleBackground::LinearGradient { angle, stops } => {
+                let stops = stops.each_ref().map(
+                    |stop| serde_json::json!({ "color": stop.color, "position": stop.position }),
+                );
+                serde_json::json!({ "linear_gradient": { "angle": angle, "stops": stops } })
+                    .serialize(serializer)
+            }
+            StyleBackground::Image { path, fit } => {
+                serde_json::json!({ "image": path, "fit": fit }).serialize(serializer)
+            }
+        }
+    }
+}
+
+impl StyleBackground {
+    fn background(&self) -> Option<Background> {
+        match self {
+            StyleBackground::Solid(color) => color.rgba().map(Background::from),
+            StyleBackground::LinearGradient {
+                angle,
+                stops: [from, to],
+            } => Some(linear_gradient(
+                *angle,
+                linear_color_stop(from.color.rgba()?, from.position),
+                linear_color_stop(to.color.rgba()?, to.position),
+            )),
+            StyleBackground::Image { .. } => None,
+        }
+    }
+
+    fn with_theme_colors(self, palette: &ThemePalette) -> Option<StyleBackground> {
+        match self {
+            StyleBackground::Solid(color) => {
+                color.with_theme_colors(palette).map(StyleBackground::Solid)
+            }
+            StyleBackground::LinearGradient {
+                angle,
+                stops: [from, to],
+            } => {
+                let stop = |stop: GradientStop| {
+                    stop.color
+                        .with_theme_colors(palette)
+                        .map(|color| GradientStop {
+                            color,
+                            position: stop.position,
+                        })
+                };
+                Some(StyleBackground::LinearGradient {
+                    angle,
+                    stops: [stop(from)?, stop(to)?],
+                })
+            }
+            StyleBackground::Image { .. } => Some(self),
+        }
+    }
+}
+
+// The image of a `background`. The images are read with the styles, see
+// `MyStyleData::with_source`; the styles watcher reloads the styles when the
+// file or one of its images changes, which reads them again.
+#[derive(Debug, Clone)]
+pub struct BackgroundImage {
+    pub image: Arc<Image>,
+    pub fit: StyleObjectFit,
+}
+
+impl BackgroundImage {
+    fn read(path: &Path) -> Option<Arc<Image>> {
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let format = match extension.as_str() {
+            "jpg" => Some(ImageFormat::Jpeg),
+            "svg" => Some(ImageFormat::Svg),
+            "tif" => Some(ImageFormat::Tiff),
+            extension => ImageFormat::from_mime_type(&format!("image/{}", extension)),
+        };
+        let Some(format) = format else {
+            tracing::error!("Unsupported background image format: {}", path.display());
+            return None;
+        };
+        match std::fs::read(path) {
+            Ok(bytes) => Some(Arc::new(Image::from_bytes(format, bytes))),
+            Err(e) => {
+                tracing::error!("Failed to read background image {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    fn element(&self) -> Img {
+        img(self.image.clone())
+            .absolute()
+            .top_0()
+            .left_0()
+            .size_full()
+            .object_fit(self.fit.into())
+    }
+}
+
+// `shadow` is one of gpui's `shadow_*` presets, a single box shadow or a list
+// of them, e.g. `[{ offset_y: 2, blur: 4, color: "#0000001a" }]`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "ShadowValue")]
+pub enum StyleShadow {
+    Preset(ShadowPreset),
+    Custom(Vec<StyleBoxShadow>),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ShadowValue {
+    Preset(String),
+    One(serde_json::Value),
+}
+
+impl Serialize for StyleShadow {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            StyleShadow::Preset(preset) => preset.serialize(serializer),
+            StyleShadow::Custom(shadows) => shadows.serialize(serializer),
+        }
+    }
+}
+
+impl TryFrom<ShadowValue> for StyleShadow {
+    type Error = serde_json::Error;
+
+    fn try_from(value: ShadowValue) -> Result<Self, Self::Error> {
+        use serde::de::IntoDeserializer;
+
+        match value {
+            ShadowValue::Preset(s) => ShadowPreset::deserialize(s.into_deserializer())
+                .map(StyleShadow::Preset)
+                .map_err(|e: serde::de::value::Error| serde::de::Error::custom(e)),
+            ShadowValue::One(serde_json::Value::Array(list)) => list
+                .into_iter()
+                .map(serde_json::from_value)
+                .collect::<Result<_, _>>()
+                .map(StyleShadow::Custom),
+            ShadowValue::One(one) => {
+                serde_json::from_value(one).map(|s| StyleShadow::Custom(vec![s]))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct StyleBoxShadow {
+    #[serde(default)]
+    pub offset_x: f32,
+    #[serde(default)]
+    pub offset_y: f32,
+    #[serde(default)]
+    pub blur: f32,
+    #[serde(default)]
+    pub spread: f32,
+    // Defaults to black at 25% opacity.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<StyleColor>,
+}
+
+impl StyleBoxShadow {
+    fn box_shadow(&self) -> Option<BoxShadow> {
+        let color = match &self.color {
+            Some(color) => color.rgba()?.into(),
+            None => hsla(0., 0., 0., 0.25),
+        };
+        Some(BoxShadow {
+            color,
+            offset: point(px(self.offset_x), px(self.offset_y)),
+            blur_radius: px(self.blur),
+            spread_radius: px(self.spread),
+        })
+    }
+}
+
+// gpui's `shadow_*` presets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ShadowPreset {
+    #[serde(rename = "none")]
+    None,
+    #[serde(rename = "2xs")]
+    Xs2,
+    #[serde(rename = "xs")]
+    Xs,
+    #[serde(rename = "sm")]
+    Sm,
+    #[serde(rename = "md")]
+    Md,
+    #[serde(rename = "lg")]
+    Lg,
+    #[serde(rename = "xl")]
+    Xl,
+    #[serde(rename = "2xl")]
+    Xl2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StyleTextAlign {
+    Left,
+    Center,
+    Right,
+}
+
+impl From<StyleTextAlign> for TextAlign {
+    fn from(value: StyleTextAlign) -> Self {
+        match value {
+            StyleTextAlign::Left => TextAlign::Left,
+            StyleTextAlign::Center => TextAlign::Center,
+            StyleTextAlign::Right => TextAlign::Right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StyleWhitespace {
+    Normal,
+    Nowrap,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StylePosition {
+    Relative,
+    Absolute,
+}
+
+// `scroll` only makes an element scrollable when it has an id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StyleOverflow {
+    Visible,
+    Clip,
+    Hidden,
+    Scroll,
+}
+
+impl From<StyleOverflow> for Overflow {
+    fn from(value: StyleOverflow) -> Self {
+        match value {
+            StyleOverflow::Visible => Overflow::Visible,
+            StyleOverflow::Clip => Overflow::Clip,
+            StyleOverflow::Hidden => Overflow::Hidden,
+            StyleOverflow::Scroll => Overflow::Scroll,
+        }
+    }
+}
+
+// CSS cursor names, mapped like gpui's `cursor_*` methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StyleCursor {
+    Default,
+    Pointer,
+    Text,
+    Move,
+    NotAllowed,
+    ContextMenu,
+    Crosshair,
+    VerticalText,
+    Alias,
+    Copy,
+    NoDrop,
+    Grab,
+    Grabbing,
+    EwResize,
+    NsResize,
+    NeswResize,
+    NwseResize,
+    ColResize,
+    RowResize,
+    NResize,
+    EResize,
+    SResize,
+    WResize,
+    None,
+}
+
+impl From<StyleCursor> for CursorStyle {
+    fn from(value: StyleCursor) -> Self {
+        match value {
+            StyleCursor::Default => CursorStyle::Arrow,
+            StyleCursor::Pointer => CursorStyle::PointingHand,
+            StyleCursor::Text => CursorStyle::IBeam,
+            StyleCursor::Move => CursorStyle::ClosedHand,
+            StyleCursor::NotAllowed => CursorStyle::OperationNotAllowed,
+            StyleCursor::ContextMenu => CursorStyle::ContextualMenu,
+            StyleCursor::Crosshair => CursorStyle::Crosshair,
+            StyleCursor::VerticalText => CursorStyle::IBeamCursorForVerticalLayout,
+            StyleCursor::Alias => CursorStyle::DragLink,
+            StyleCursor::Copy => CursorStyle::DragCopy,
+            StyleCursor::NoDrop => CursorStyle::OperationNotAllowed,
+            StyleCursor::Grab => CursorStyle::OpenHand,
+            StyleCursor::Grabbing => CursorStyle::ClosedHand,
+            StyleCursor::EwResize => CursorStyle::ResizeLeftRight,
+            StyleCursor::NsResize => CursorStyle::ResizeUpDown,
+            StyleCursor::NeswResize => CursorStyle::ResizeUpRightDownLeft,
+            StyleCursor::NwseResize => CursorStyle::ResizeUpLeftDownRight,
+            StyleCursor::ColResize => CursorStyle::ResizeColumn,
+            StyleCursor::RowResize => CursorStyle::ResizeRow,
+            StyleCursor::NResize => CursorStyle::ResizeUp,
+            StyleCursor::EResize => CursorStyle::ResizeRight,
+            StyleCursor::SResize => CursorStyle::ResizeDown,
+            StyleCursor::WResize => CursorStyle::ResizeLeft,
+            StyleCursor::None => CursorStyle::None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StyleBorderStyle {
+    Solid,
+    Dashed,
+}
+
+impl From<StyleBorderStyle> for BorderStyle {
+    fn from(value: StyleBorderStyle) -> Self {
+        match value {
+            StyleBorderStyle::Solid => BorderStyle::Solid,
+            StyleBorderStyle::Dashed => BorderStyle::Dashed,
+        }
+    }
+}
+
+// Named minimum window widths, the same as Tailwind's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+pub enum Breakpoint {
+    #[serde(rename = "sm")]
+    Sm,
+    #[serde(rename = "md")]
+    Md,
+    #[serde(rename = "lg")]
+    Lg,
+    #[serde(rename = "xl")]
+    Xl,
+    #[serde(rename = "2xl")]
+    Xl2,
+}
+
+impl Breakpoint {
+    pub const ALL: [Breakpoint; 5] = [
+        Breakpoint::Sm,
+        Breakpoint::Md,
+        Breakpoint::Lg,
+        Breakpoint::Xl,
+        Breakpoint::Xl2,
+    ];
+
+    pub fn min_width(self) -> f32 {
+        match self {
+            Breakpoint::Sm => 640.,
+            Breakpoint::Md => 768.,
+            Breakpoint::Lg => 1024.,
+            Breakpoint::Xl => 1280.,
+            Breakpoint::Xl2 => 1536.,
+        }
+    }
+
+    pub fn query(self) -> MediaQuery {
+        MediaQuery {
+            min_width: Some(self.min_width()),
+            ..Default::default()
+        }
+    }
+}
+
+// The light or dark variant of the stylesheet, following `gpui_component::Theme`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StyleTheme {
+    #[default]
+    Light,
+    Dark,
+}
+
+impl StyleTheme {
+    // The mode of the global `Theme`, or light when it isn't set up yet.
+    pub fn current(cx: &App) -> Self {
+        match cx.try_global::<Theme>() {
+            Some(theme) if theme.mode.is_dark() => StyleTheme::Dark,
+            _ => StyleTheme::Light,
+        }
+    }
+}
+
+impl std::str::FromStr for StyleTheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "light" => Ok(StyleTheme::Light),
+            "dark" => Ok(StyleTheme::Dark),
+            other => Err(format!(
+                "unknown theme `{}`, expected `light` or `dark`",
+                other
+            )),
+        }
+    }
+}
+
+// What `@media` conditions are matched against. Without a known viewport
+// (before the first `update_viewport`) no size condition matches.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct MediaState {
+    pub viewport: Option<Size<Pixels>>,
+    pub theme: StyleTheme,
+}
+
+// The condition of a `@media(...)` block, e.g. `@media(max_width: 600)`,
+// `@media(min_width: 600) and (max_height: 400)` or `@media(theme: dark)`.
+// Sizes are in pixels and the bounds are inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct MediaQuery {
+    pub min_width: Option<f32>,
+    pub max_width: Option<f32>,
+    pub min_height: Option<f32>,
+    pub max_height: Option<f32>,
+    pub theme: Option<StyleTheme>,
+}
+
+impl MediaQuery {
+    pub fn matches(&self, state: &MediaState) -> bool {
+        let size_matches = match state.viewport {
+            Some(viewport) => {
+                self.min_width.is_none_or(|w| viewport.width >= px(w))
+                    && self.max_width.is_none_or(|w| viewport.width <= px(w))
+                    && self.min_height.is_none_or(|h| viewport.height >= px(h))
+                    && self.max_height.is_none_or(|h| viewport.height <= px(h))
+            }
+            None => {
+                self.min_width.is_none()
+                    && self.max_width.is_none()
+                    && self.min_height.is_none()
+                    && self.max_height.is_none()
+            }
+        };
+        size_matches && self.theme.is_none_or(|theme| theme == state.theme)
+    }
+}
+
+impl std::str::FromStr for MediaQuery {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let conditions = s
+            .trim()
+            .strip_prefix("@media")
+            .ok_or_else(|| "a media query must start with `@media`".to_string())?;
+
+        let mut query = MediaQuery::default();
+        for condition in conditions.split(" and ") {
+            let condition = condition
+                .trim()
+                .strip_prefix('(')
+                .and_then(|c| c.strip_suffix(')'))
+                .ok_or_else(|| format!("expected `(feature: value)`, found `{}`", condition))?;
+            let (feature, value) = condition
+                .split_once(':')
+                .ok_or_else(|| format!("expected `(feature: value)`, found `({})`", condition))?;
+            let value = value.trim();
+
+            let target = match feature.trim().replace('-', "_").as_str() {
+                "theme" => {
+                    query.theme = Some(value.parse()?);
+                    continue;
+                }
+                "min_width" => &mut query.min_width,
+                "max_width" => &mut query.max_width,
+                "min_height" => &mut query.min_height,
+                "max_height" => &mut query.max_height,
+                other => {
+                    return Err(format!(
+                        "unknown feature `{}`, expected one of `min_width`, `max_width`, `min_height`, `max_height`, `theme`",
+                        other
+                    ))
+                }
+            };
+            *target = Some(
+                value
+                    .trim_end_matches("px")
+                    .parse()
+                    .map_err(|_| format!("invalid value `{}`", value))?,
+            );
+        }
+        Ok(query)
+    }
+}
+
+// A corner radius: a number of pixels or one of gpui's `rounded_*` presets.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "LengthValue")]
+pub enum StyleRadius {
+    Px(f32),
+    Preset(RadiusPreset),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum RadiusPreset {
+    #[serde(rename = "none")]
+    None,
+    #[serde(rename = "xs")]
+    Xs,
+    #[serde(rename = "sm")]
+    Sm,
+    #[serde(rename = "md")]
+    Md,
+    #[serde(rename = "lg")]
+    Lg,
+    #[serde(rename = "xl")]
+    Xl,
+    #[serde(rename = "2xl")]
+    Xl2,
+    #[serde(rename = "3xl")]
+    Xl3,
+    #[serde(rename = "full")]
+    Full,
+}
+
+impl Serialize for StyleRadius {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            StyleRadius::Px(n) => serializer.serialize_f32(*n),
+            StyleRadius::Preset(preset) => preset.serialize(serializer),
+        }
+    }
+}
+
+impl TryFrom<LengthValue> for StyleRadius {
+    type Error = serde::de::value::Error;
+
+    fn try_from(value: LengthValue) -> Result<Self, Self::Error> {
+        use serde::de::IntoDeserializer;
+
+        match value {
+            LengthValue::Number(n) => Ok(StyleRadius::Px(n)),
+            LengthValue::Text(s) => match s.parse::<f32>() {
+                Ok(n) => Ok(StyleRadius::Px(n)),
+                Err(_) => RadiusPreset::deserialize(s.into_deserializer()).map(StyleRadius::Preset),
+            },
+        }
+    }
+}
+
+impl From<StyleRadius> for AbsoluteLength {
+    // Same values as gpui's `rounded_*` methods.
+    fn from(radius: StyleRadius) -> Self {
+        match radius {
+            StyleRadius::Px(v) => px(v).into(),
+            StyleRadius::Preset(preset) => match preset {
+                RadiusPreset::None => px(0.).into(),
+                RadiusPreset::Xs => rems(0.125).into(),
+                RadiusPreset::Sm => rems(0.25).into(),
+                RadiusPreset::Md => rems(0.375).into(),
+                RadiusPreset::Lg => rems(0.5).into(),
+                RadiusPreset::Xl => rems(0.75).into(),
+                RadiusPreset::Xl2 => rems(1.).into(),
+                RadiusPreset::Xl3 => rems(1.5).into(),
+                RadiusPreset::Full => px(9999.).into(),
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MyStyleData {
+    pub style_map: StyleMap,
+    // Rules of the top-level `selectors` object, in file order.
+    pub selectors: Vec<SelectorRule>,
+    // Resolved styles by cache key, see `resolve` and `resolve_element_keyed`,
+    // filled on first use. Dropped when the theme or a media condition changes;
+    // past `MAX_RESOLVED` the oldest styles are dropped. A reload creates a new
+    // `MyStyleData` with an empty cache.
+    resolved: RefCell<ResolvedCache>,
+    // The window size and theme breakpoints and `@media` blocks are matched against.
+    media_state: Cell<MediaState>,
+    // Colors of the active theme, for `"theme.*"` colors.
+    palette: RefCell<ThemePalette>,
+    // Every distinct `@media` condition in the stylesheet.
+    media_queries: Vec<MediaQuery>,
+    // Styles of the elements with a `transition`, see `StylableElement::transition`.
+    transitions: Transitions,
+    // The styles file, when loaded from one.
+    source: Option<PathBuf>,
+    // The background images of the rules by path, read by `with_source`.
+    images: HashMap<PathBuf, Arc<Image>>,
+    // Ignored state sub-rules already warned about, see `report_ignored_states`.
+    reported: RefCell<HashSet<String>>,
+}
+
+impl MyStyleData {
+    pub fn new(style_map: StyleMap) -> Self {
+        let mut media_queries: Vec<MediaQuery> =
+            Breakpoint::ALL.iter().map(|b| b.query()).collect();
+        for rule in style_map.values() {
+            for (query, _) in &rule.media {
+                if !media_queries.contains(query) {
+                    media_queries.push(*query);
+                }
+            }
+        }
+
+        Self {
+            style_map,
+            selectors: Vec::new(),
+            resolved: Default::default(),
+            media_state: Cell::new(MediaState::default()),
+            palette: Default::default(),
+            media_queries,
+            transitions: Default::default(),
+            source: None,
+            images: HashMap::new(),
+            reported: Default::default(),
+        }
+    }
+
+    pub fn with_selectors(mut self, selectors: Vec<SelectorRule>) -> Self {
+        self.selectors = selectors;
+        self
+    }
+
+    // Background image paths are relative to the directory of `path`. The
+    // images are read here, so never while rendering; styles without a source
+    // have no background images.
+    pub fn with_source(mut self, path: PathBuf) -> Self {
+        self.source = Some(path);
+        self.images = self
+            .background_images()
+            .into_iter()
+            .filter_map(|path| BackgroundImage::read(&path).map(|image| (path, image)))
+            .collect();
+        self
+    }
+
+    // The file these styles were loaded from, e.g. to write edits back to.
+    pub fn source(&self) -> Option<&Path> {
+        self.source.as_deref()
+    }
+
+    fn image_path(&self, path: &str) -> PathBuf {
+        match self.source.as_deref().and_then(Path::parent) {
+            Some(dir) => dir.join(path),
+            None => PathBuf::from(path),
+        }
+    }
+
+    // The background image files of all rules, for the styles watcher.
+    fn background_images(&self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        let rules = self
+            .style_map
+            .values()
+            .chain(self.selectors.iter().map(|s| &s.rule));
+        for rule in rules {
+            let media = rule.media.iter().map(|(_, rule)| rule);
+            let breakpoints = rule.breakpoints.iter().flatten().map(|(_, rule)| &**rule);
+            for rule in std::iter::once(rule).chain(media).chain(breakpoints) {
+                if let Some(StyleBackground::Image { path, .. }) = &rule.background {
+                    paths.push(self.image_path(path));
+                }
+            }
+        }
+        paths
+    }
+
+    // Carry the transition state and the window size of the styles being
+    // replaced over to these, so that a reload animates instead of jumping and
+    // keeps matching the same breakpoints.
+    pub fn keep_state_of(&self, previous: &MyStyleData) {
+        self.transitions.take_from(&previous.transitions);
+        self.set_media_state(MediaState {
+            viewport: previous.media_state.get().viewport,
+            ..self.media_state.get()
+        });
+    }
+
+    // Match breakpoints and `@media` blocks against the window size.
+    // `observe_viewport` does this whenever the window is resized.
+    pub fn update_viewport(&self, window: &Window) {
+        self.set_media_state(MediaState {
+            viewport: Some(window.viewport_size()),
+            ..self.media_state.get()
+        });
+    }
+
+    // Follow the size of `window`, for the styles of the view `cx` belongs to:
+    // `init_style_data(cx, path).observe_viewport(window, cx)`. Without it no
+    // breakpoint or `@media` size condition matches.
+    pub fn observe_viewport<T>(self, window: &mut Window, cx: &mut Context<T>) -> Self
+    where
+        T: SetMyStyleData + 'static,
+    {
+        self.update_viewport(window);
+        cx.observe_window_bounds(window, |this, window, cx| {
+            this.get_style_data().update_viewport(window);
+            cx.notify();
+        })
+        .detach();
+        self
+    }
+
+    // Switch to the light or dark variant and the colors of the global `Theme`.
+    // `init_style_data` does this whenever the theme changes.
+    pub fn sync_theme(&self, cx: &App) {
+        self.set_media_state(MediaState {
+            theme: StyleTheme::current(cx),
+            ..self.media_state.get()
+        });
+
+        let palette = cx
+            .try_global::<Theme>()
+            .map(theme_palette)
+            .unwrap_or_default();
+        if *self.palette.borrow() != palette {
+            self.palette.replace(palette);
+            self.resolved.borrow_mut().clear();
+        }
+    }
+
+    // The cache is only dropped when a condition flips.
+    fn set_media_state(&self, state: MediaState) {
+        let old = self.media_state.replace(state);
+        if self
+            .media_queries
+            .iter()
+            .any(|q| q.matches(&old) != q.matches(&state))
+        {
+            self.resolved.borrow_mut().clear();
+        }
+    }
+
+    // Calls `f` with the resolved style of `classes` plus the optional inline
+    // `style` rule, resolving and caching it on the first call.
+    pub fn resolve<R>(
+        &self,
+        classes: &str,
+        inline: Option<&serde_json::Value>,
+        f: impl FnOnce(&ResolvedStyle) -> R,
+    ) -> R {
+        let key = StyleKey::new(
+            &[SelectorTarget {
+                classes,
+                ..Default::default()
+            }],
+            inline,
+        );
+        self.resolve_matched(&key, classes, Vec::new, inline, f)
+    }
+
+    // Like `resolve`, for an element of a pjson layout: `path` is the element
+    // last, preceded by its ancestors. The `selectors` rules matching it are
+    // merged too, by specificity; those less specific than a class (element
+    // types only) before the classes, the others after them.
+    pub fn resolve_element<R>(
+        &self,
+        path: &[SelectorTarget],
+        inline: Option<&serde_json::Value>,
+        f: impl FnOnce(&ResolvedStyle) -> R,
+    ) -> R {
+        self.resolve_element_keyed(&StyleKey::new(path, inline), path, inline, f)
+    }
+
+    // Same as `resolve_element`, with the cache `key` of `path` and `inline`
+    // computed beforehand, e.g. when the layout was loaded. Selectors are only
+    // matched when the style is not cached yet.
+    pub fn resolve_element_keyed<R>(
+        &self,
+        key: &StyleKey,
+        path: &[SelectorTarget],
+        inline: Option<&serde_json::Value>,
+        f: impl FnOnce(&ResolvedStyle) -> R,
+    ) -> R {
+        let classes = path.last().map(|target| target.classes).unwrap_or("");
+        let matched = || {
+            let mut matched: Vec<usize> = (0..self.selectors.len())
+                .filter(|i| self.selectors[*i].selector.matches(path))
+                .collect();
+            // Stable, so equal specificities keep the file order.
+            matched.sort_by_key(|i| self.selectors[*i].selector.specificity());
+            matched
+        };
+        self.resolve_matched(key, classes, matched, inline, f)
+    }
+
+    // `matched` returns indices into `selectors`, sorted by specificity.
+    fn resolve_matched<R>(
+        &self,
+        key: &StyleKey,
+        classes: &str,
+        matched: impl FnOnce() -> Vec<usize>,
+        inline: Option<&serde_json::Value>,
+        f: impl FnOnce(&ResolvedStyle) -> R,
+    ) -> R {
+        // Cloned out of the cache, as `f` may resolve other styles.
+        let cached = self.resolved.borrow().get(key);
+        if let Some(resolved) = cached {
+            return f(&resolved);
+        }
+
+        let matched = matched();
+        let inline_rule: Option<StyleRule> =
+            inline.and_then(|styles| match serde_json::from_value(styles.clone()) {
+                Ok(style_rule) => Some(style_rule),
+                Err(e) => {
+                    tracing::error!("wrong style: {}", e);
+                    None
+                }
+            });
+        let selector_rules = |less_specific_than_class: bool| {
+            matched
+                .iter()
+                .map(|i| &self.selectors[*i])
+                .filter(move |s| {
+                    (s.selector.specificity() < Specificity::CLASS) == less_specific_than_class
+                })
+                .map(|s| &s.rule)
+        };
+        // Classes missing from the styles may be utility classes such as `p-4`.
+        let class_rules: Vec<Cow<StyleRule>> = classes
+            .split(' ')
+            .filter_map(|class| match self.style_map.get(class) {
+                Some(rule) => Some(Cow::Borrowed(rule)),
+                None => match parse_utility_class(class)? {
+                    Ok(rule) => Some(Cow::Owned(rule)),
+                    Err(e) => {
+                        tracing::warn!("{}", e);
+                        None
+                    }
+                },
+            })
+            .collect();
+        // Breakpoints and `@media` blocks are resolved per rule, so that they
+        // only override the rule they belong to and not the later ones.
+        let media_state = self.media_state.get();
+        let rules: Vec<StyleRule> = selector_rules(true)
+            .chain(class_rules.iter().map(|rule| rule.as_ref()))
+            .chain(selector_rules(false))
+            .chain(inline_rule.as_ref())
+            .map(|rule| rule.clone().for_media(&media_state))
+            .collect();
+        let rule = StyleRule::merge_all(&rules).with_theme_colors(&self.palette.borrow());
+
+        let mut resolved = ResolvedStyle::from_rule(&rule);
+        if let Some(StyleBackground::Image { path, fit }) = &rule.background {
+            resolved.background_image =
+                self.images
+                    .get(&self.image_path(path))
+                    .map(|image| BackgroundImage {
+                        image: image.clone(),
+                        fit: *fit,
+                    });
+        }
+        let resolved = Arc::new(resolved);
+        self.resolved.borrow_mut().insert(key, resolved.clone());
+        f(&resolved)
+    }
+
+    // Warn about the `hover`, `active` and `focus` sub-rules of `classes` that
+    // `element` has no gpui refinement for, once per `MyStyleData`, i.e. once
+    // per reload.
+    pub fn report_ignored_states(
+        &self,
+        element: &str,
+        classes: &str,
+        resolved: &ResolvedStyle,
+        states: ElementStates,
+    ) {
+        let ignored = [
+            ("hover", &resolved.hover, ElementStates::Interactive),
+            ("focus", &resolved.focus, ElementStates::Interactive),
+            ("active", &resolved.active, ElementStates::Stateful),
+        ];
+        for (state, refinement, needed) in ignored {
+            if refinement.is_none() || states >= needed {
+                continue;
+            }
+            let key = format!("{} {} {}", element, classes, state);
+            if self.reported.borrow_mut().insert(key) {
+                tracing::warn!(
+                    "The `{}` sub-rule of `{}` is ignored: `{}` has no gpui {} style",
+                    state,
+                    classes,
+                    element,
+                    state
+                );
+            }
+        }
+    }
+
+    // Record the resolved style of element `id`, returning the transition to run
+    // when one of its `transition` properties changed.
+    pub fn resolve_transition(
+        &self,
+        id: &str,
+        classes: &str,
+        inline: Option<&serde_json::Value>,
+    ) -> Option<RunningTransition> {
+        self.resolve(classes, inline, |resolved| self.transition_of(id, resolved))
+    }
+
+    // Record `resolved` as the style of element `id`, see `resolve_transition`.
+    pub fn transition_of(&self, id: &str, resolved: &ResolvedStyle) -> Option<RunningTransition> {
+        self.transitions.update(id, &resolved.rule)
+    }
+}
+
+// Bound of the resolved style cache, which inline styles built in code could
+// otherwise grow without end.
+const MAX_RESOLVED: usize = 4096;
+
+// The cache key of an element's style: what it is resolved from besides the
+// styles, and its hash. The inputs are compared on a hit, so that a colliding
+// hash resolves the style again instead of using another element's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyleKey {
+    hash: u64,
+    inputs: String,
+}
+
+impl StyleKey {
+    pub fn new(path: &[SelectorTarget], inline: Option<&serde_json::Value>) -> Self {
+        // Debug formatting quotes the strings, so that distinct inputs never
+        // run together into the same text.
+        let mut inputs = String::new();
+        for target in path {
+            inputs.push_str(&format!(
+                "{:?} ",
+                (target.element, target.id, target.classes)
+            ));
+        }
+        if let Some(inline) = inline {
+            inputs.push_str(&inline.to_string());
+        }
+
+        let mut hasher = DefaultHasher::new();
+        inputs.hash(&mut hasher);
+        Self {
+            hash: hasher.finish(),
+            inputs,
+        }
+    }
+}
+
+// Resolved styles by key. Past `MAX_RESOLVED` the oldest ones are dropped.
+#[derive(Debug, Default)]
+struct ResolvedCache {
+    styles: HashMap<u64, (String, Arc<ResolvedStyle>)>,
+    // Hashes in insertion order.
+    order: VecDeque<u64>,
+}
+
+impl ResolvedCache {
+    fn get(&self, key: &StyleKey) -> Option<Arc<ResolvedStyle>> {
+        self.styles
+            .get(&key.hash)
+            .filter(|(inputs, _)| *inputs == key.inputs)
+            .map(|(_, resolved)| resolved.clone())
+    }
+
+    // A colliding key replaces the style cached under its hash.
+    fn insert(&mut self, key: &StyleKey, resolved: Arc<ResolvedStyle>) {
+        let entry = (key.inputs.clone(), resolved);
+        if self.styles.insert(key.hash, entry).is_none() {
+            self.order.push_back(key.hash);
+        }
+        while self.styles.len() > MAX_RESOLVED {
+            if let Some(oldest) = self.order.pop_front() {
+                self.styles.remove(&oldest);
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.styles.clear();
+        self.order.clear();
+    }
+}
+
+// A cascaded `StyleRule` turned into gpui refinements, ready to be applied.
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedStyle {
+    pub base: StyleRefinement,
+    pub hover: Option<StyleRefinement>,
+    pub active: Option<StyleRefinement>,
+    pub focus: Option<StyleRefinement>,
+    pub disabled: Option<StyleRefinement>,
+    pub background_image: Option<BackgroundImage>,
+    // The cascaded rule itself, for transitions.
+    pub rule: StyleRule,
+}
+
+impl ResolvedStyle {
+    // Without the background image, which only `MyStyleData` has read.
+    pub fn from_rule(rule: &StyleRule) -> Self {
+        let refinement = |rule: &Option<Box<StyleRule>>| {
+            rule.as_ref()
+                .map(|rule| apply_rule_properties(StyleRefinement::default(), rule))
+        };
+
+        ResolvedStyle {
+            base: apply_rule_properties(StyleRefinement::default(), rule),
+            hover: refinement(&rule.hover),
+            active: refinement(&rule.active),
+            focus: refinement(&rule.focus),
+            disabled: refinement(&rule.disabled),
+            background_image: None,
+            rule: rule.clone(),
+        }
+    }
+}
+
+pub type StyleMap = HashMap<String, StyleRule>;
+
+pub fn load_styles(path: &PathBuf) -> anyhow::Result<MyStyleData> {
+    let content = std::fs::read(path.as_path())?;
+    Ok(parse_styles(&content)?.with_source(path.clone()))
+}
+
+// Parse the pjson content of a styles file.
+pub fn parse_styles(content: &[u8]) -> anyhow::Result<MyStyleData> {
+    let json = pjson::PJsonReader::from_pjson(content);
+    let json = String::from_utf8_lossy(&json).to_string();
+    let (styles, selectors) = parse_style_sheet(&json)?;
+
+    Ok(MyStyleData::new(styles).with_selectors(selectors))
+}
+
+// The class rules of a styles file, see `parse_style_sheet`.
+pub fn parse_style_map(json: &str) -> anyhow::Result<StyleMap> {
+    parse_style_sheet(json).map(|(styles, _)| styles)
+}
+
+// Parse the (already pjson-decoded) content of a styles file.
+// The optional top-level `vars` object defines variables, which can be used as
+// `"$name"` in place of any property value.
+// The optional top-level `selectors` object holds rules keyed by a `Selector`
+// (`button`, `#btn_add`, `.sidebar label`, ...) for the elements of pjson layouts.
+pub fn parse_style_sheet(json: &str) -> anyhow::Result<(StyleMap, Vec<SelectorRule>)> {
+    let mut root: serde_json::Map<String, serde_json::Value> = serde_json::from_str(json)?;
+
+    let vars = match root.remove("vars") {
+        Some(serde_json::Value::Object(vars)) => vars,
+        Some(_) => anyhow::bail!("`vars` must be an object"),
+        None => serde_json::Map::new(),
+    };
+
+    for (class, value) in root.iter_mut() {
+        resolve_vars(value, &vars, class, "")?;
+    }
+
+    let media_keys: Vec<String> = root
+        .keys()
+        .filter(|key| key.starts_with("@media"))
+        .cloned()
+        .collect();
+    let media_blocks: Vec<_> = media_keys
+        .into_iter()
+        .filter_map(|key| root.remove(&key).map(|value| (key, value)))
+        .collect();
+
+    let themes = match root.remove("themes") {
+        Some(serde_json::Value::Object(themes)) => themes,
+        Some(_) => anyhow::bail!("`themes` must be an object"),
+        None => serde_json::Map::new(),
+    };
+
+    let selector_block = match root.remove("selectors") {
+        Some(serde_json::Value::Object(selectors)) => selectors,
+        Some(_) => anyhow::bail!("`selectors` must be an object"),
+        None => serde_json::Map::new(),
+    };
+
+    let mut styles = StyleMap::new();
+    for class in root.keys() {
+        let value = flatten_extends(class, &root, &mut vec![])?;
+        styles.insert(class.clone(), parse_style_rule(class, value)?);
+    }
+
+    let mut selectors = Vec::new();
+    for (key, value) in selector_block {
+        let selector: Selector = key
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid selector `{}`: {}", key, e))?;
+        // Selector rules may extend classes as well.
+        let name = format!("selectors.{}", key);
+        let mut scope = root.clone();
+        scope.insert(name.clone(), value);
+        let value = flatten_extends(&name, &scope, &mut vec![])?;
+        let rule = parse_style_rule(&name, value)?;
+        selectors.push(SelectorRule { selector, rule });
+    }
+
+    // `themes: { dark: { class: {...} } }` overlays and
+    // `"@media(max_width: 600)": { class: {...} }` blocks, attached to their classes.
+    let themes = themes.into_iter().map(|(theme, block)| {
+        let query = theme.parse().map(|theme| MediaQuery {
+            theme: Some(theme),
+            ..Default::default()
+        });
+        (format!("themes.{}", theme), query, block)
+    });
+    let media_blocks = media_blocks.into_iter().map(|(key, block)| {
+        let query = key.parse::<MediaQuery>();
+        (key, query, block)
+    });
+    for (key, query, block) in themes.chain(media_blocks) {
+        let query = query.map_err(|e| anyhow::anyhow!("Invalid `{}`: {}", key, e))?;
+        let serde_json::Value::Object(block) = block else {
+            anyhow::bail!("`{}` must be an object of classes", key);
+        };
+        for (class, value) in block {
+            let rule = parse_style_rule(&class, value).with_context(|| format!("In `{}`", key))?;
+            styles.entry(class).or_default().media.push((query, rule));
+        }
+    }
+
+    Ok((styles, selectors))
+}
+
+fn parse_style_rule(class: &str, value: serde_json::Value) -> Result<StyleRule> {
+    let err = match serde_json::from_value(value.clone()) {
+        Ok(rule) => return Ok(rule),
+        Err(err) => err,
+    };
+
+    // serde doesn't tell which property failed, so check them one by one.
+    if let serde_json::Value::Object(map) = value {
+        for (property, v) in map {
+            let single = serde_json::Value::Object([(property.clone(), v)].into_iter().collect());
+            if let Err(e) = serde_json::from_value::<StyleRule>(single) {
+                anyhow::bail!(
+                    "Invalid style class `{}`, property `{}`: {}",
+                    class,
+                    property,
+                    e
+                );
+            }
+        }
+    }
+
+    Err(err).with_context(|| format!("Invalid style class `{}`", class))
+}
+
+// Resolve `extends: "parent"` or `extends: ["a", "b"]` of a class into one json object.
+// Parents are merged in order, then the class's own properties override them.
+fn flatten_extends(
+    class: &str,
+    classes: &serde_json::Map<String, serde_json::Value>,
+    chain: &mut Vec<String>,
+) -> Result<serde_json::Value> {
+    if chain.iter().any(|c| c == class) {
+        chain.push(class.to_string());
+        anyhow::bail!("Cyclic `extends`: {}", chain.join(" -> "));
+    }
+
+    let mut own = classes
+        .get(class)
+        .cloned()
+        .with_context(|| format!("Missing class `{}`", class))?;
+    let parents = match own.as_object_mut().and_then(|o| o.remove("extends")) {
+        None => vec![],
+        Some(serde_json::Value::String(parent)) => vec![parent],
+        Some(serde_json::Value::Array(parents)) => parents
+            .into_iter()
+            .map(|p| match p {
+                serde_json::Value::String(p) => Ok(p),
+                _ => anyhow::bail!(
+                    "`extends` of class `{}` must only contain class names",
+                    class
+                ),
+            })
+            .collect::<Result<_>>()?,
+        Some(_) => anyhow::bail!(
+            "`extends` of class `{}` must be a class name or a list of class names",
+            class
+        ),
+    };
+
+    let mut flattened = serde_json::Value::Object(serde_json::Map::new());
+    chain.push(class.to_string());
+    for parent in parents {
+        if !classes.contains_key(&parent) {
+            anyhow::bail!("Class `{}` extends missing class `{}`", class, parent);
+        }
+        merge_json(&mut flattened, flatten_extends(&parent, classes, chain)?);
+    }
+    chain.pop();
+    merge_json(&mut flattened, own);
+
+    Ok(flattened)
+}
+
+// Objects (such as `hover` sub-rules) are merged key by key, everything else is replaced.
+fn merge_json(target: &mut serde_json::Value, source: serde_json::Value) {
+    match (target, source) {
+        (serde_json::Value::Object(target), serde_json::Value::Object(source)) => {
+            for (key, value) in source {
+                match target.get_mut(&key) {
+                    Some(t) if t.is_object() && value.is_object() => merge_json(t, value),
+                    _ => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, source) => *target = source,
+    }
+}
+
+fn resolve_vars(
+    value: &mut serde_json::Value,
+    vars: &serde_json::Map<String, serde_json::Value>,
+    class: &str,
+    property: &str,
+) -> Result<()> {
+    match value {
+        serde_json::Value::String(s) => {
+            if let Some(name) = s.strip_prefix('$') {
+                *value = lookup_var(name, vars)
+                    .with_context(|| format!("In class `{}`, property `{}`", class, property))?;
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                let property = if property.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", property, key)
+                };
+                resolve_vars(v, vars, class, &property)?;
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for (i, v) in items.iter_mut().enumerate() {
+                resolve_vars(v, vars, class, &format!("{}[{}]", property, i))?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+// A variable may itself refer to another variable, e.g. `primary: "$blue"`.
+fn lookup_var(
+    name: &str,
+    vars: &serde_json::Map<String, serde_json::Value>,
+) -> Result<serde_json::Value> {
+    let mut name = name;
+    let mut seen = vec![];
+    loop {
+        if seen.contains(&name) {
+            anyhow::bail!("Cyclic variable `${}`", name);
+        }
+        seen.push(name);
+
+        match vars.get(name) {
+            Some(serde_json::Value::String(s)) if s.starts_with('$') => name = &s[1..],
+            Some(v) => return Ok(v.clone()),
+            None => anyhow::bail!("Undefined variable `${}`", name),
+        }
+    }
+}
+
+// A size keyword (`xs` .. `3xl`), pixels (`"16"`, `"16px"`) or rems (`"1.25rem"`).
+// `xs` and `3xl` are gpui's `text_xs` and `text_3xl`. `sm` .. `2xl` keep the
+// pixel sizes they had before gpui's scale was added, which existing styles
+// rely on; gpui's sizes for them are 0.875, 1, 1.125, 1.25 and 1.5 rems.
+fn parse_font_size(size: &str) -> Result<AbsoluteLength, String> {
+    let size = size.trim();
+    let length = match size {
+        "xs" => rems(0.75).into(),
+        "sm" => px(12.0).into(),
+        "base" => px(16.0).into(),
+        "lg" => px(20.0).into(),
+        "xl" => px(24.0).into(),
+        "2xl" => px(32.0).into(),
+        "3xl" => rems(1.875).into(),
+        _ => {
+            let invalid = || {
+                format!(
+                    "invalid font size `{}`, expected pixels, rems or one of \
+                     `xs`, `sm`, `base`, `lg`, `xl`, `2xl`, `3xl`",
+                    size
+                )
+            };
+            if let Some(r) = size.strip_suffix("rem") {
+                rems(r.trim().parse().map_err(|_| invalid())?).into()
+            } else {
+                let p = size.strip_suffix("px").unwrap_or(size);
+                px(p.trim().parse().map_err(|_| invalid())?).into()
+            }
+        }
+    };
+    Ok(length)
+}
+
+// Which of gpui's state refinements an element has: `hover` and `focus` come
+// with `InteractiveElement`, `active` with `StatefulInteractiveElement` (elements
+// with an id). A plain `Styled` element has none of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ElementStates {
+    Plain,
+    Interactive,
+    Stateful,
+}
+
+// Add the `img` child drawing a background image, if `el` is a div.
+fn apply_background_image<T: 'static>(el: T, image: &BackgroundImage) -> T {
+    fn apply<E: ParentElement + 'static>(slot: &mut dyn Any, image: &BackgroundImage) {
+        if let Some(slot) = slot.downcast_mut::<Option<E>>() {
+            *slot = slot.take().map(|el| el.child(image.element()));
+        }
+    }
+
+    let mut slot = Some(el);
+    apply::<Div>(&mut slot, image);
+    apply::<Stateful<Div>>(&mut slot, image);
+    slot.expect("the element is put back")
+}
+
+// Any `Styled` element takes classes. Their `hover`, `active` and `focus`
+// sub-rules need gpui's interactive elements, see `interactive_class` and
+// `stateful_class`; `class` reports them as ignored.
+pub trait StylableElement: Sized + Styled {
+    fn class(self, classes: impl Into<String>, style_data: &MyStyleData) -> Self {
+        self.class_disabled(classes, false, style_data)
+    }
+
+    // Same as `class`, but when `disabled` is true the `disabled` sub-rules are applied
+    // on top.
+    fn class_disabled(
+        self,
+        classes: impl Into<String>,
+        disabled: bool,
+        style_data: &MyStyleData,
+    ) -> Self {
+        let classes: String = classes.into();
+        style_data.resolve(&classes, None, |resolved| {
+            let element = std::any::type_name::<Self>();
+            style_data.report_ignored_states(element, &classes, resolved, ElementStates::Plain);
+            self.apply_resolved_style(resolved, disabled)
+        })
+    }
+
+    // Same as `class_disabled`, plus the `hover` (unless `disabled`) and `focus`
+    // sub-rules.
+    fn interactive_class(
+        self,
+        classes: impl Into<String>,
+        disabled: bool,
+        style_data: &MyStyleData,
+    ) -> Self
+    where
+        Self: InteractiveElement,
+    {
+        let classes: String = classes.into();
+        style_data.resolve(&classes, None, |resolved| {
+            let element = std::any::type_name::<Self>();
+            let states = ElementStates::Interactive;
+            style_data.report_ignored_states(element, &classes, resolved, states);
+            self.apply_resolved_style(resolved, disabled)
+                .apply_interactive_states(resolved, disabled)
+        })
+    }
+
+    // Same as `interactive_class`, plus the `active` sub-rule (unless `disabled`),
+    // which gpui only tracks on elements with an id.
+    fn stateful_class(
+        self,
+        classes: impl Into<String>,
+        disabled: bool,
+        style_data: &MyStyleData,
+    ) -> Self
+    where
+        Self: StatefulInteractiveElement,
+    {
+        let classes: String = classes.into();
+        style_data.resolve(&classes, None, |resolved| {
+            self.apply_resolved_style(resolved, disabled)
+                .apply_interactive_states(resolved, disabled)
+                .apply_active_state(resolved, disabled)
+        })
+    }
+
+    // Same as `class`, but changes of the properties listed in the `transition` of
+    // the resolved style are animated. `id` must be unique among the elements
+    // using transitions, as their previous style is looked up by it.
+    fn class_transition(
+        self,
+        id: &str,
+        classes: impl Into<String>,
+        style_data: &MyStyleData,
+    ) -> AnyElement
+    where
+        Self: IntoElement + 'static,
+    {
+        let classes: String = classes.into();
+        self.class(classes.as_str(), style_data)
+            .transition(id, &classes, None, style_data)
+    }
+
+    // Same as `stateful_class`, but changes of the properties listed in the
+    // `transition` of the resolved style are animated, including those coming
+    // from its `hover` and `active` sub-rules. `id` is as for `class_transition`.
+    fn stateful_class_transition(
+        self,
+        id: &str,
+        classes: impl Into<String>,
+        disabled: bool,
+        style_data: &MyStyleData,
+    ) -> AnyElement
+    where
+        Self: StatefulInteractiveElement + IntoElement + 'static,
+    {
+        let classes: String = classes.into();
+        style_data.resolve(&classes, None, |resolved| {
+            self.apply_resolved_style(resolved, disabled)
+                .transition_states(id, resolved, disabled, style_data)
+        })
+    }
+
+    // The `hover`, `active` and `focus` sub-rules of `resolved`, on an element that
+    // already has the rest of it applied. With a `transition`, the `hover` and
+    // `active` ones are applied while the element is in that state, instead of by
+    // gpui, so that the change can be animated like any other.
+    fn transition_states(
+        self,
+        id: &str,
+        resolved: &ResolvedStyle,
+        disabled: bool,
+        style_data: &MyStyleData,
+    ) -> AnyElement
+    where
+        Self: StatefulInteractiveElement + IntoElement + 'static,
+    {
+        let rule = &resolved.rule;
+        if rule.transition.is_none() {
+            return self
+                .apply_interactive_states(resolved, disabled)
+                .apply_active_state(resolved, disabled)
+                .into_any_element();
+        }
+
+        let transitions = &style_data.transitions;
+        let mut el = transitions.track_pointer(id, self);
+        if let Some(focus) = resolved.focus.clone() {
+            el = el.focus(|_| focus);
+        }
+        let mut target = rule.clone();
+        if let Some(rule) = rule.disabled.as_deref().filter(|_| disabled) {
+            target.merge(rule);
+        }
+        for rule in transitions.pointer_rules(id, rule, disabled) {
+            el = apply_rule_properties(el, rule);
+            target.merge(rule);
+        }
+        match transitions.update(id, &target) {
+            Some(transition) => transition.animate(el),
+            None => el.into_any_element(),
+        }
+    }
+
+    // Animate changes of the resolved style of `classes` plus the optional inline
+    // `style` rule; the element must already have that style applied.
+    fn transition(
+        self,
+        id: &str,
+        classes: &str,
+        inline: Option<&serde_json::Value>,
+        style_data: &MyStyleData,
+    ) -> AnyElement
+    where
+        Self: IntoElement + 'static,
+    {
+        match style_data.resolve_transition(id, classes, inline) {
+            Some(transition) => transition.animate(self),
+            None => self.into_any_element(),
+        }
+    }
+
+    fn apply_style_rule_json(self, value: serde_json::value::Value) -> Self;
+    fn apply_style_rule(self, rule: &StyleRule) -> Self;
+    fn apply_style_rules(self, rules: &[&StyleRule], disabled: bool) -> Self;
+    // The base properties and, when `disabled`, the `disabled` sub-rule; the
+    // other sub-rules are applied by `apply_interactive_states` and
+    // `apply_active_state`.
+    fn apply_resolved_style(self, resolved: &ResolvedStyle, disabled: bool) -> Self;
+
+    fn apply_interactive_states(mut self, resolved: &ResolvedStyle, disabled: bool) -> Self
+    where
+        Self: InteractiveElement,
+    {
+        if let Some(hover) = resolved.hover.clone().filter(|_| !disabled) {
+            self = self.hover(|_| hover);
+        }
+        if let Some(focus) = resolved.focus.clone() {
+            self = self.focus(|_| focus);
+        }
+        self
+    }
+
+    fn apply_active_state(self, resolved: &ResolvedStyle, disabled: bool) -> Self
+    where
+        Self: StatefulInteractiveElement,
+    {
+        match resolved.active.clone().filter(|_| !disabled) {
+            Some(active) => self.active(|_| active),
+            None => self,
+        }
+    }
+}
+
+impl<T> StylableElement for T
+where
+    T: Styled + 'static,
+{
+    fn apply_style_rule_json(mut self, value: serde_json::value::Value) -> Self {
+        self = self.apply_style_rule(&serde_json::from_value(value).unwrap());
+        self
+    }
+
+    fn apply_style_rule(self, rule: &StyleRule) -> Self {
+        self.apply_style_rules(&[rule], false)
+    }
+
+    fn apply_style_rules(self, rules: &[&StyleRule], disabled: bool) -> Self {
+        // Cascade first, so each property is applied once with its final value.
+        let rule = StyleRule::merge_all(rules.iter().copied());
+        self.apply_resolved_style(&ResolvedStyle::from_rule(&rule), disabled)
+    }
+
+    fn apply_resolved_style(mut self, resolved: &ResolvedStyle, disabled: bool) -> Self {
+        self.style().refine(&resolved.base);
+
+        if let Some(disabled_style) = resolved.disabled.as_ref().filter(|_| disabled) {
+            self.style().refine(disabled_style);
+        }
+
+        match &resolved.background_image {
+            Some(image) => apply_background_image(self, image),
+            None => self,
+        }
+    }
+}
+
+pub(crate) fn apply_rule_properties<T: Styled>(mut el: T, rule: &StyleRule) -> T {
+    if let Some(size_full) = rule.size_full {
+        if size_full {
+            el = el.size_full();
+        }
+    }
+    if let Some(bg) = rule.bg_color.as_ref().and_then(StyleColor::rgba) {
+        el = el.bg(bg);
+    }
+    if let Some(bg) = rule
+        .background
+        .as_ref()
+        .and_then(StyleBackground::background)
+    {
+        el = el.bg(bg);
+    }
+    if let Some(c) = rule.text_color.as_ref().and_then(StyleColor::rgba) {
+        el = el.text_color(c);
+    }
+    if let Some(fs) = rule.font_size {
+        el = el.text_size(fs.0);
+    }
+    if let Some(font_weight) = rule.font_weight {
+        el = el.font_weight(FontWeight(font_weight.0));
+    }
+    if let Some(family) = &rule.font_family {
+        el = el.font_family(SharedString::from(family.clone()));
+    }
+    if let Some(lh) = rule
+        .line_height
+        .and_then(|lh| definite_length(lh, "line_height"))
+    {
+        el = el.line_height(lh);
+    }
+    if let Some(align) = rule.text_align {
+        el = el.text_align(align.into());
+    }
+    match rule.italic {
+        Some(true) => el = el.italic(),
+        Some(false) => el = el.not_italic(),
+        None => {}
+    }
+    if rule.underline == Some(true) {
+        el = el.underline();
+    }
+    if rule.line_through == Some(true) {
+        el = el.line_through();
+    }
+    match rule.whitespace {
+        Some(StyleWhitespace::Normal) => el = el.whitespace_normal(),
+        Some(StyleWhitespace::Nowrap) => el = el.whitespace_nowrap(),
+        None => {}
+    }
+    if rule.text_ellipsis == Some(true) {
+        el = el.text_ellipsis();
+    }
+    if let Some(lines) = rule.line_clamp {
+        el = el.line_clamp(lines);
+    }
+    if let Some(display) = rule.display {
+        el.style().display = Some(display.into());
+    }
+    if let Some(jc) = rule.justify_content {
+        el.style().justify_content = Some(jc.into());
+    }
+    if let Some(ai) = rule.align_items {
+        el.style().align_items = Some(ai.into());
+    }
+    if let Some(a) = rule.align_self {
+        el.style().align_self = Some(a.into());
+    }
+    if let Some(fd) = rule.flex_direction {
+        el.style().flex_direction = Some(fd.into());
+    }
+    if let Some(flex_grow) = rule.flex_grow {
+        el.style().flex_grow = Some(flex_grow);
+    }
+    if let Some(flex_shrink) = rule.flex_shrink {
+        el.style().flex_shrink = Some(flex_shrink);
+    }
+
+    if let Some(flex_basis) = rule.flex_basis {
+        el = el.flex_basis(flex_basis);
+    }
+
+    if let Some(w) = rule.width {
+        el = el.w(w);
+    }
+    if let Some(h) = rule.height {
+        el = el.h(h);
+    }
+    if let Some(min_width) = rule.min_width {
+        el = el.min_w(min_width)
+    }
+    if let Some(min_height) = rule.min_height {
+        el = el.min_h(min_height)
+    }
+    if let Some(max_width) = rule.max_width {
+        el = el.max_w(max_width)
+    }
+    if let Some(max_height) = rule.max_height {
+        el = el.max_h(max_height)
+    }
+
+    match rule.position {
+        Some(StylePosition::Relative) => el = el.relative(),
+        Some(StylePosition::Absolute) => el = el.absolute(),
+        None => {}
+    }
+    if let Some(top) = rule.top.or(rule.inset) {
+        el = el.top(top);
+    }
+    if let Some(right) = rule.right.or(rule.inset) {
+        el = el.right(right);
+    }
+    if let Some(bottom) = rule.bottom.or(rule.inset) {
+        el = el.bottom(bottom);
+    }
+    if let Some(left) = rule.left.or(rule.inset) {
+        el = el.left(left);
+    }
+
+    if let Some(opacity) = rule.opacity {
+        el = el.opacity(opacity);
+    }
+    match rule.visible {
+        Some(true) => el = el.visible(),
+        Some(false) => el = el.invisible(),
+        None => {}
+    }
+    if let Some(cursor) = rule.cursor {
+        el.style().mouse_cursor = Some(cursor.into());
+    }
+    if let Some(overflow) = rule.overflow_x {
+        el.style().overflow.x = Some(overflow.into());
+    }
+    if let Some(overflow) = rule.overflow_y {
+        el.style().overflow.y = Some(overflow.into());
+    }
+
+    if let Some(shadow) = &rule.shadow {
+        el = match shadow {
+            StyleShadow::Preset(ShadowPreset::None) => el.shadow_none(),
+            StyleShadow::Preset(ShadowPreset::Xs2) => el.shadow_2xs(),
+            StyleShadow::Preset(ShadowPreset::Xs) => el.shadow_xs(),
+            StyleShadow::Preset(ShadowPreset::Sm) => el.shadow_sm(),
+            StyleShadow::Preset(ShadowPreset::Md) => el.shadow_md(),
+            StyleShadow::Preset(ShadowPreset::Lg) => el.shadow_lg(),
+            StyleShadow::Preset(ShadowPreset::Xl) => el.shadow_xl(),
+            StyleShadow::Preset(ShadowPreset::Xl2) => el.shadow_2xl(),
+            StyleShadow::Custom(shadows) => {
+                el.shadow(shadows.iter().filter_map(|s| s.box_shadow()).collect())
+            }
+        };
+    }
+    if let Some(bw) = rule.border_width {
+        el = el.border(px(bw));
+    }
+    if let Some(bw) = rule.border_top {
+        el = el.border_t(px(bw));
+    }
+    if let Some(bw) = rule.border_right {
+        el = el.border_r(px(bw));
+    }
+    if let Some(bw) = rule.border_bottom {
+        el = el.border_b(px(bw));
+    }
+    if let Some(bw) = rule.border_left {
+        el = el.border_l(px(bw));
+    }
+    if let Some(bc) = rule.border_color.as_ref().and_then(StyleColor::rgba) {
+        el = el.border_color(bc);
+    }
+    if let Some(bs) = rule.border_style {
+        el.style().border_style = Some(bs.into());
+    }
+    if let Some(g) = rule.gap.and_then(|g| definite_length(g, "gap")) {
+        el = el.gap(g);
+    }
+    if let Some(g) = rule.gap_x.and_then(|g| definite_length(g, "gap_x")) {
+        el.style().gap.width = Some(g);
+    }
+    if let Some(g) = rule.gap_y.and_then(|g| definite_length(g, "gap_y")) {
+        el.style().gap.height = Some(g);
+    }
+
+    if let Some(cols) = rule.grid_cols {
+        el = el.grid_cols(cols);
+    }
+    if let Some(rows) = rule.grid_rows {
+        el = el.grid_rows(rows);
+    }
+    // Spans first, as they set both ends of the placement.
+    match rule.col_span {
+        Some(GridSpan::Tracks(span)) => el = el.col_span(span),
+        Some(GridSpan::Full) => el = el.col_span_full(),
+        None => {}
+    }
+    match rule.row_span {
+        Some(GridSpan::Tracks(span)) => el = el.row_span(span),
+        Some(GridSpan::Full) => el = el.row_span_full(),
+        None => {}
+    }
+    if let Some(start) = rule.col_start {
+        el = el.col_start(start);
+    }
+    if let Some(end) = rule.col_end {
+        el = el.col_end(end);
+    }
+    if let Some(start) = rule.row_start {
+        el = el.row_start(start);
+    }
+    if let Some(end) = rule.row_end {
+        el = el.row_end(end);
+    }
+    if let Some(rounded) = rule.rounded {
+        el = el.rounded(rounded);
+    }
+    if let Some(rounded) = rule.rounded_tl {
+        el = el.rounded_tl(rounded);
+    }
+    if let Some(rounded) = rule.rounded_tr {
+        el = el.rounded_tr(rounded);
+    }
+    if let Some(rounded) = rule.rounded_bl {
+        el = el.rounded_bl(rounded);
+    }
+    if let Some(rounded) = rule.rounded_br {
+        el = el.rounded_br(rounded);
+    }
+
+    // Set margin and padding. Only the edges a rule actually specifies are
+    // touched, so a later class (or a `hover` sub-rule) without margins keeps
+    // the ones set before it.
+    if let Some(top) = rule.margin_top.or(rule.margin) {
+        el = el.mt(top);
+    }
+    if let Some(right) = rule.margin_right.or(rule.margin) {
+        el = el.mr(right);
+    }
+    if let Some(bottom) = rule.margin_bottom.or(rule.margin) {
+        el = el.mb(bottom);
+    }
+    if let Some(left) = rule.margin_left.or(rule.margin) {
+        el = el.ml(left);
+    }
+
+    let padding = |side: Option<StyleLength>| {
+        side.or(rule.padding)
+            .and_then(|p| definite_length(p, "padding"))
+    };
+    if let Some(top) = padding(rule.padding_top) {
+        el = el.pt(top);
+    }
+    if let Some(right) = padding(rule.padding_right) {
+        el = el.pr(right);
+    }
+    if let Some(bottom) = padding(rule.padding_bottom) {
+        el = el.pb(bottom);
+    }
+    if let Some(left) = padding(rule.padding_left) {
+        el = el.pl(left);
+    }
+
+    el
+}
+
+pub fn init_style_data<T: 'static>(cx: &mut Context<T>, style_path: String) -> MyStyleData
+where
+    T: SetMyStyleData,
+{
+    // If the style_path file doesn't exist, replace it with ../../{style_path}
+    let style_path = if std::path::Path::new(&style_path).exists() {
+        style_path
+    } else {
+        format!("../../{}", style_path)
+    };
+
+    let style_path2 = style_path.clone();
+
+    let style_data: MyStyleData = match load_styles(&PathBuf::from(style_path)) {
+        Ok(data) => {
+            tracing::info!("✅ Styles reloaded successfully.");
+            data
+        }
+        Err(e) => {
+            tracing::error!("{:?}", e);
+            thread::sleep(std::time::Duration::from_secs(1));
+            panic!("Failed to load styles.")
+        }
+    };
+
+    style_data.sync_theme(cx);
+
+    watch_style_data(cx, style_path2);
+
+    cx.observe_global::<Theme>(|this, cx| {
+        this.get_style_data().sync_theme(cx);
+        cx.notify();
+    })
+    .detach();
+
+    style_data
+}
+
+pub trait SetMyStyleData {
+    fn set_style_data(&mut self, data: MyStyleData);
+    fn get_style_data(&self) -> &MyStyleData;
+}
+
+fn watch_style_data<T: 'static>(cx: &mut Context<T>, style_path: String)
+where
+    T: SetMyStyleData,
+{
+    let (th_sender, mut th_receiver) = futures::channel::mpsc::channel::<MyStyleData>(100); // std::sync::mpsc::channel::<MyStyleData>();
+
+    let be = cx.background_executor().clone();
+
+    std::thread::spawn(|| {
+        if let Err(e) = run_watcher(PathBuf::from(style_path), be, th_sender) {
+            tracing::error!("File watcher failed: {:?}", e);
+        }
+    });
+
+    cx.my_spawn(async move |entity, cx| {
+        loop {
+            match th_receiver.next().await {
+                Some(r) => {
+                    tracing::info!("Refresh window");
+
+                    entity
+                        .upgrade()
+                        .context("entity upgrade fail.")?
+                        .update(cx, |this, cx| {
+                            r.sync_theme(cx);
+                            r.keep_state_of(this.get_style_data());
+                            this.set_style_data(r);
+                            cx.notify(); // Must notify UI to update
+                        })?;
+                }
+                None => {}
+            }
+        }
+
+        #[allow(unreachable_code)]
+        Ok(())
+    })
+    .detach();
+}
+
+// File watcher (hot reload). Background images are watched too, as they are
+// read with the styles.
+fn run_watcher(
+    path: PathBuf,
+    be: BackgroundExecutor,
+    mut sender: futures::channel::mpsc::Sender<MyStyleData>,
+) -> Result<()> {
+    use notify::{recommended_watcher, RecursiveMode, Watcher};
+
+    let (event_sender, events) = std::sync::mpsc::channel();
+    let mut watcher = recommended_watcher(event_sender)?;
+    watcher.watch(&path, RecursiveMode::NonRecursive)?;
+
+    let mut images = Vec::new();
+    let mut watch_images = |watcher: &mut notify::RecommendedWatcher, style_data: &MyStyleData| {
+        for image in style_data.background_images() {
+            if !images.contains(&image) {
+                match watcher.watch(&image, RecursiveMode::NonRecursive) {
+                    Ok(()) => images.push(image),
+                    Err(e) => tracing::error!("Failed to watch {}: {:?}", image.display(), e),
+                }
+            }
+        }
+    };
+    if let Ok(style_data) = load_styles(&path) {
+        watch_images(&mut watcher, &style_data);
+    }
+
+    for res in events {
+        match res {
+            Ok(_) => match load_styles(&path) {
+                Ok(style_data) => {
+                    tracing::info!("✅ Styles reloaded successfully.");
+                    watch_images(&mut watcher, &style_data);
+                    be.block(async {
+                        let _ = sender.send(style_data).await;
+                    });
+                }
+                Err(e) => {
+                    tracing::error!("{:?}", e);
+                    tracing::warn!("Keeping the previous styles until the file is fixed.");
+                }
+            },
             Err(e) => tracing::error!("Watch error: {:?}", e),
-        })?;
+        }
+    }
+    Ok(())
+}
 
-    watcher.watch(&path2, RecursiveMode::NonRecursive)?;
-    loop {
-        std::thread::park(); // keep alive, prevent watcher from being dropped.
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_font_size, parse_style_map, parse_style_sheet, GridSpan, MediaQuery, MediaState,
+        MyStyleData, RadiusPreset, ResolvedStyle, ShadowPreset, StylableElement, StyleBackground,
+        StyleBoxShadow, StyleColor, StyleDisplay, StyleFontSize, StyleFontWeight, StyleKey,
+        StyleLength, StyleObjectFit, StylePosition, StyleRadius, StyleRule, StyleShadow,
+        StyleTheme, ThemePalette, MAX_RESOLVED,
+    };
+    use crate::style_selector::SelectorTarget;
+    use crate::style_utility::parse_utility_class;
+    use gpui::{
+        div, point, px, rems, rgb, size, AbsoluteLength, App, Bounds, Context, CursorStyle, Div,
+        DivFrameState, Element, ElementId, GlobalElementId, Hitbox, InspectorElementId,
+        InteractiveElement, IntoElement, LayoutId, Modifiers, MouseButton, Overflow, ParentElement,
+        Pixels, Render, StyleRefinement, Styled, TestAppContext, Window,
+    };
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_parse_style_map_vars() {
+        let json = r##"{
+            "vars": { "primary": "#3366ff", "accent": "$primary", "space_md": 8 },
+            "btn": { "bg_color": "$accent", "padding": "$space_md", "hover": { "text_color": "$primary" } }
+        }"##;
+        let styles = parse_style_map(json).unwrap();
+        let btn = &styles["btn"];
+        assert_eq!(btn.bg_color, Some(StyleColor::Rgba(rgb(0x3366ff))));
+        assert_eq!(btn.padding, Some(StyleLength::Px(8.0)));
+        assert_eq!(
+            btn.hover.as_ref().unwrap().text_color,
+            Some(StyleColor::Rgba(rgb(0x3366ff)))
+        );
+
+        let err =
+            parse_style_map(r#"{ "btn": { "hover": { "bg_color": "$missing" } } }"#).unwrap_err();
+        let err = format!("{:#}", err);
+        assert!(
+            err.contains("`btn`") && err.contains("`hover.bg_color`") && err.contains("$missing")
+        );
+    }
+
+    #[test]
+    fn test_parse_style_map_extends() {
+        let json = r##"{
+            "base": { "padding": 5, "bg_color": "#ffffff", "hover": { "bg_color": "#eeeeee" } },
+            "wide": { "width": 200 },
+            "btn": { "extends": ["base", "wide"], "bg_color": "#ff0000", "hover": { "text_color": "#000000" } }
+        }"##;
+        let styles = parse_style_map(json).unwrap();
+        let btn = &styles["btn"];
+        assert_eq!(btn.padding, Some(StyleLength::Px(5.0)));
+        assert_eq!(btn.width, Some(StyleLength::Px(200.0)));
+        assert_eq!(btn.bg_color, Some(StyleColor::Rgba(rgb(0xff0000))));
+        let hover = btn.hover.as_ref().unwrap();
+        assert_eq!(hover.bg_color, Some(StyleColor::Rgba(rgb(0xeeeeee))));
+        assert_eq!(hover.text_color, Some(StyleColor::Rgba(rgb(0x000000))));
+
+        let err = parse_style_map(r#"{ "a": { "extends": "b" }, "b": { "extends": "a" } }"#);
+        assert!(format!("{:#}", err.unwrap_err()).contains("Cyclic"));
+
+        let err = parse_style_map(r#"{ "a": { "extends": "nope" } }"#);
+        assert!(format!("{:#}", err.unwrap_err()).contains("missing class `nope`"));
+    }
+
+    #[test]
+    fn test_style_length() {
+        let parse = |s: &str| s.parse::<StyleLength>();
+        assert_eq!(parse("12"), Ok(StyleLength::Px(12.0)));
+        assert_eq!(parse("12px"), Ok(StyleLength::Px(12.0)));
+        assert_eq!(parse("1.5rem"), Ok(StyleLength::Rems(1.5)));
+        assert_eq!(parse("50%"), Ok(StyleLength::Relative(0.5)));
+        assert_eq!(parse("1/4"), Ok(StyleLength::Fraction(1.0, 4.0)));
+        assert_eq!(parse("1/4").unwrap().relative(), Some(0.25));
+        assert_eq!(parse("full"), Ok(StyleLength::Full));
+        assert_eq!(parse("auto"), Ok(StyleLength::Auto));
+        assert!(parse("12em").is_err());
+
+        let styles =
+            parse_style_map(r#"{ "a": { "width": "50%", "margin_left": "auto", "gap": 4 } }"#)
+                .unwrap();
+        assert_eq!(styles["a"].width, Some(StyleLength::Relative(0.5)));
+        assert_eq!(styles["a"].margin_left, Some(StyleLength::Auto));
+        assert_eq!(styles["a"].gap, Some(StyleLength::Px(4.0)));
+        assert!(parse_style_map(r#"{ "a": { "width": "wide" } }"#).is_err());
+    }
+
+    #[test]
+    fn test_invalid_color_names_class_and_property() {
+        let styles = parse_style_map(r#"{ "a": { "bg_color": "hsl(210, 50%, 40%)" } }"#).unwrap();
+        assert!(styles["a"].bg_color.is_some());
+
+        let err = parse_style_map(r##"{ "a": { "width": 10, "text_color": "#12345" } }"##);
+        let err = format!("{:#}", err.unwrap_err());
+        assert!(err.contains("class `a`") && err.contains("property `text_color`"));
+    }
+
+    #[test]
+    fn test_style_radius() {
+        let styles = parse_style_map(
+            r#"{ "a": { "rounded": "xs", "rounded_tl": 6, "rounded_br": "2xl" } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            styles["a"].rounded,
+            Some(StyleRadius::Preset(RadiusPreset::Xs))
+        );
+        assert_eq!(styles["a"].rounded_tl, Some(StyleRadius::Px(6.0)));
+        assert_eq!(
+            styles["a"].rounded_br,
+            Some(StyleRadius::Preset(RadiusPreset::Xl2))
+        );
+
+        let err = parse_style_map(r#"{ "a": { "rounded": "huge" } }"#).unwrap_err();
+        assert!(format!("{:#}", err).contains("expected one of"));
+    }
+
+    #[test]
+    fn test_keyword_properties() {
+        let styles = parse_style_map(r#"{ "a": { "display": "none", "shadow": "2xl" } }"#).unwrap();
+        assert_eq!(styles["a"].display, Some(StyleDisplay::None));
+        assert_eq!(
+            styles["a"].shadow,
+            Some(StyleShadow::Preset(ShadowPreset::Xl2))
+        );
+
+        let err = parse_style_map(r#"{ "a": { "justify_content": "centre" } }"#).unwrap_err();
+        let msg = format!("{:#}", err);
+        assert!(
+            msg.contains("class `a`, property `justify_content`"),
+            "{msg}"
+        );
+        assert!(msg.contains("`space-between`"), "{msg}");
+    }
+
+    #[test]
+    fn test_style_rule_merge() {
+        let styles = parse_style_map(
+            r#"{
+                "a": { "margin": 4, "padding_top": 2, "hover": { "bg_color": "red" } },
+                "b": { "margin_left": 8, "width": 10, "hover": { "text_color": "blue" } },
+                "c": { "padding": 6 }
+            }"#,
+        )
+        .unwrap();
+
+        let merged = StyleRule::merge_all([&styles["a"], &styles["b"]]);
+        assert_eq!(merged.margin, Some(StyleLength::Px(4.0)));
+        assert_eq!(merged.margin_left, Some(StyleLength::Px(8.0)));
+        assert_eq!(merged.padding_top, Some(StyleLength::Px(2.0)));
+        assert_eq!(merged.width, Some(StyleLength::Px(10.0)));
+        let hover = merged.hover.unwrap();
+        assert!(hover.bg_color.is_some() && hover.text_color.is_some());
+
+        // A later shorthand overrides earlier per-side values.
+        let merged = StyleRule::merge_all([&styles["a"], &styles["c"]]);
+        assert_eq!(merged.padding_top, None);
+        assert_eq!(merged.padding, Some(StyleLength::Px(6.0)));
+    }
+
+    #[test]
+    fn test_resolved_style_cache() {
+        let styles =
+            parse_style_map(r#"{ "a": { "width": 10 }, "b": { "hover": { "bg_color": "red" } } }"#)
+                .unwrap();
+        let style_data = MyStyleData::new(styles);
+
+        let has_hover = style_data.resolve("a b", None, |resolved| resolved.hover.is_some());
+        assert!(has_hover);
+        style_data.resolve("a b", None, |_| ());
+        let inline = serde_json::json!({ "height": 5 });
+        style_data.resolve("a b", Some(&inline), |resolved| {
+            assert!(resolved.base.size.height.is_some());
+        });
+        assert_eq!(style_data.resolved.borrow().styles.len(), 2);
+
+        // A colliding hash doesn't hand out the style of other inputs.
+        let a = SelectorTarget {
+            element: "div",
+            id: None,
+            classes: "a",
+        };
+        let b = SelectorTarget { classes: "b", ..a };
+        let key_a = StyleKey::new(&[a], None);
+        let key_b = StyleKey {
+            hash: key_a.hash,
+            ..StyleKey::new(&[b], None)
+        };
+        style_data.resolve_element_keyed(&key_a, &[a], None, |_| ());
+        let has_hover = style_data.resolve_element_keyed(&key_b, &[b], None, |r| r.hover.is_some());
+        assert!(has_hover);
+
+        // Resolving another style while using a cached one.
+        style_data.resolve("a b", None, |_| style_data.resolve("c", None, |_| ()));
+
+        // The cache is bounded, dropping the oldest styles first.
+        for width in 0..MAX_RESOLVED {
+            let inline = serde_json::json!({ "width": width });
+            style_data.resolve("a", Some(&inline), |_| ());
+        }
+        let cache = style_data.resolved.borrow();
+        assert_eq!(cache.styles.len(), MAX_RESOLVED);
+        assert!(cache.get(&StyleKey::new(&[b], None)).is_none());
+        let last_inline = serde_json::json!({ "width": MAX_RESOLVED - 1 });
+        let last = SelectorTarget {
+            classes: "a",
+            ..Default::default()
+        };
+        assert!(cache
+            .get(&StyleKey::new(&[last], Some(&last_inline)))
+            .is_some());
+    }
+
+    #[test]
+    fn test_media_queries() {
+        let styles = parse_style_map(
+            r#"{
+                "a": { "width": 10, "breakpoints": { "md": { "width": 20 } } },
+                "@media(max_width: 600) and (min-height: 100)": { "a": { "width": 5 } }
+            }"#,
+        )
+        .unwrap();
+        let rule = &styles["a"];
+
+        let width = |w: f32| {
+            let state = MediaState {
+                viewport: Some(size(px(w), px(300.))),
+                ..Default::default()
+            };
+            rule.clone().for_media(&state).width
+        };
+        assert_eq!(width(500.), Some(StyleLength::Px(5.0)));
+        assert_eq!(width(700.), Some(StyleLength::Px(10.0)));
+        assert_eq!(width(800.), Some(StyleLength::Px(20.0)));
+        assert_eq!(
+            rule.clone().for_media(&MediaState::default()).width,
+            Some(StyleLength::Px(10.0))
+        );
+
+        assert!("@media(max_wdth: 600)".parse::<MediaQuery>().is_err());
+    }
+
+    #[test]
+    fn test_media_cascade() {
+        let styles = parse_style_map(
+            r#"{
+                "a": { "width": 10, "breakpoints": { "md": { "width": 20 } } },
+                "b": { "width": 30 }
+            }"#,
+        )
+        .unwrap();
+        let style_data = MyStyleData::new(styles);
+        style_data.set_media_state(MediaState {
+            viewport: Some(size(px(800.), px(600.))),
+            ..Default::default()
+        });
+
+        // A matching block overrides its own class, not later classes or the inline style.
+        let width = |classes: &str, inline: Option<&serde_json::Value>| {
+            style_data.resolve(classes, inline, |resolved| resolved.base.size.width)
+        };
+        assert_eq!(width("a", None), Some(px(20.).into()));
+        assert_eq!(width("a b", None), Some(px(30.).into()));
+        let inline = serde_json::json!({ "width": 40 });
+        assert_eq!(width("a", Some(&inline)), Some(px(40.).into()));
+    }
+
+    #[test]
+    fn test_themes() {
+        let styles = parse_style_map(
+            r#"{
+                "a": { "bg_color": "white", "width": 10 },
+                "themes": { "dark": { "a": { "bg_color": "black" } } },
+                "@media(theme: light)": { "a": { "width": 20 } }
+            }"#,
+        )
+        .unwrap();
+
+        let light = styles["a"].clone().for_media(&MediaState::default());
+        assert_eq!(light.bg_color, Some(StyleColor::Rgba(rgb(0xffffff))));
+        assert_eq!(light.width, Some(StyleLength::Px(20.0)));
+
+        let dark = styles["a"].clone().for_media(&MediaState {
+            theme: StyleTheme::Dark,
+            ..Default::default()
+        });
+        assert_eq!(dark.bg_color, Some(StyleColor::Rgba(rgb(0x000000))));
+        assert_eq!(dark.width, Some(StyleLength::Px(10.0)));
+
+        assert!(parse_style_map(r#"{ "themes": { "dim": {} } }"#).is_err());
+    }
+
+    #[test]
+    fn test_theme_colors() {
+        let styles = parse_style_map(
+            r#"{ "a": { "bg_color": "theme.primary", "hover": { "border_color": "theme.border" } } }"#,
+        )
+        .unwrap();
+        assert_eq!(styles["a"].bg_color, Some(StyleColor::Theme("primary")));
+
+        let palette: ThemePalette = [
+            ("primary", rgb(0x3366ff).into()),
+            ("border", rgb(0xcccccc).into()),
+        ]
+        .into_iter()
+        .collect();
+        let rule = styles["a"].clone().with_theme_colors(&palette);
+        assert_eq!(
+            rule.bg_color,
+            Some(StyleColor::Rgba(palette["primary"].into()))
+        );
+        assert_eq!(
+            rule.hover.unwrap().border_color,
+            Some(StyleColor::Rgba(palette["border"].into()))
+        );
+
+        let err = parse_style_map(r#"{ "a": { "text_color": "theme.primry" } }"#).unwrap_err();
+        assert!(format!("{:#}", err).contains("unknown theme color `primry`"));
+    }
+
+    #[test]
+    fn test_grid_properties() {
+        let styles = parse_style_map(
+            r#"{ "gallery": { "display": "grid", "grid_cols": 3, "gap_x": 8 },
+                 "wide": { "col_span": 2, "row_span": "full", "row_start": -1 } }"#,
+        )
+        .unwrap();
+        assert_eq!(styles["gallery"].grid_cols, Some(3));
+        assert_eq!(styles["wide"].col_span, Some(GridSpan::Tracks(2)));
+        assert_eq!(styles["wide"].row_span, Some(GridSpan::Full));
+        assert_eq!(styles["wide"].row_start, Some(-1));
+
+        assert!(parse_style_map(r#"{ "a": { "col_span": 0 } }"#).is_err());
+    }
+
+    #[test]
+    fn test_position_properties() {
+        let styles = parse_style_map(
+            r#"{ "overlay": { "position": "absolute", "inset": 0 },
+                 "badge": { "top": "-4px", "right": "10%" } }"#,
+        )
+        .unwrap();
+        let merged = StyleRule::merge_all([&styles["badge"], &styles["overlay"]]);
+        assert_eq!(merged.position, Some(StylePosition::Absolute));
+        assert_eq!(merged.inset, Some(StyleLength::Px(0.0)));
+        assert_eq!(merged.top, None);
+
+        let merged = StyleRule::merge_all([&styles["overlay"], &styles["badge"]]);
+        assert_eq!(merged.top, Some(StyleLength::Px(-4.0)));
+        assert_eq!(merged.right, Some(StyleLength::Relative(0.1)));
+    }
+
+    #[test]
+    fn test_typography_properties() {
+        // The keywords keep their original sizes.
+        for (keyword, size) in [
+            ("sm", 12.),
+            ("base", 16.),
+            ("lg", 20.),
+            ("xl", 24.),
+            ("2xl", 32.),
+        ] {
+            assert_eq!(parse_font_size(keyword), Ok(px(size).into()));
+        }
+        // The added ones are gpui's.
+        assert_eq!(parse_font_size("xs"), Ok(rems(0.75).into()));
+        assert_eq!(parse_font_size("3xl"), Ok(rems(1.875).into()));
+        assert_eq!(parse_font_size("16"), Ok(px(16.).into()));
+        assert_eq!(
+            parse_font_size("1.5rem"),
+            Ok(AbsoluteLength::from(rems(1.5)))
+        );
+        assert!(parse_font_size("huge").is_err());
+
+        let styles = parse_style_map(
+            r#"{ "title": { "font_size": 20, "font_weight": "semibold", "italic": true,
+                            "text_align": "center", "whitespace": "nowrap", "line_clamp": 2 } }"#,
+        )
+        .unwrap();
+        let title = &styles["title"];
+        assert_eq!(title.font_size, Some(StyleFontSize(px(20.).into())));
+        assert_eq!(title.font_weight, Some(StyleFontWeight(600.)));
+        assert_eq!(title.line_clamp, Some(2));
+
+        let err = parse_style_map(r#"{ "a": { "font_weight": "heavy" } }"#).unwrap_err();
+        assert!(format!("{:#}", err).contains("property `font_weight`"));
+    }
+
+    #[test]
+    fn test_visual_properties() {
+        let styles = parse_style_map(
+            r#"{ "a": { "opacity": 0.5, "visible": false, "cursor": "ew-resize", "overflow_y": "hidden" } }"#,
+        )
+        .unwrap();
+        let refinement = ResolvedStyle::from_rule(&styles["a"]).base;
+        assert_eq!(refinement.opacity, Some(0.5));
+        assert_eq!(refinement.mouse_cursor, Some(CursorStyle::ResizeLeftRight));
+        assert_eq!(refinement.overflow.y, Some(Overflow::Hidden));
+
+        let err = parse_style_map(r#"{ "a": { "cursor": "hand" } }"#).unwrap_err();
+        assert!(format!("{:#}", err).contains("`pointer`"));
+    }
+
+    #[test]
+    fn test_custom_shadows() {
+        let styles = parse_style_map(
+            r##"{ "card": { "shadow": [
+                    { "offset_y": 1, "blur": 3, "color": "#0000001a" },
+                    { "offset_y": 1, "blur": 2, "spread": -1, "color": "theme.border" }
+                 ] },
+                 "one": { "shadow": { "offset_x": 2, "offset_y": 2 } } }"##,
+        )
+        .unwrap();
+        let Some(StyleShadow::Custom(shadows)) = &styles["card"].shadow else {
+            panic!("expected custom shadows");
+        };
+        assert_eq!(shadows.len(), 2);
+        assert_eq!(shadows[1].spread, -1.0);
+        assert_eq!(
+            styles["one"].shadow,
+            Some(StyleShadow::Custom(vec![StyleBoxShadow {
+                offset_x: 2.0,
+                offset_y: 2.0,
+                blur: 0.0,
+                spread: 0.0,
+                color: None,
+            }]))
+        );
+
+        // Without a theme, the shadow using a theme color is dropped.
+        let card = styles["card"]
+            .clone()
+            .with_theme_colors(&ThemePalette::new());
+        assert!(matches!(card.shadow, Some(StyleShadow::Custom(s)) if s.len() == 1));
+
+        let err = parse_style_map(r#"{ "a": { "shadow": [{ "blurr": 3 }] } }"#).unwrap_err();
+        assert!(format!("{:#}", err).contains("property `shadow`"));
+        let err = parse_style_map(r#"{ "a": { "shadow": "huge" } }"#).unwrap_err();
+        assert!(format!("{:#}", err).contains("`2xl`"));
+    }
+
+    #[test]
+    fn test_background() {
+        let styles = parse_style_map(
+            r#"{ "solid": { "background": "red" },
+                 "header": { "background": { "linear_gradient": {
+                     "angle": 90, "stops": ["white", { "color": "black", "position": 0.8 }] } } } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            styles["solid"].background,
+            Some(StyleBackground::Solid(StyleColor::Rgba(rgb(0xff0000))))
+        );
+        let Some(StyleBackground::LinearGradient { angle, stops }) = &styles["header"].background
+        else {
+            panic!("expected a gradient");
+        };
+        assert_eq!(*angle, 90.0);
+        assert_eq!((stops[0].position, stops[1].position), (0.0, 0.8));
+
+        let merged = StyleRule::merge_all([
+            &styles["solid"],
+            &parse_style_map(r#"{ "a": { "bg_color": "blue" } }"#).unwrap()["a"],
+        ]);
+        assert_eq!(merged.background, None);
+
+        let err = parse_style_map(
+            r#"{ "a": { "background": { "linear_gradient": { "stops": ["red"] } } } }"#,
+        )
+        .unwrap_err();
+        assert!(format!("{:#}", err).contains("exactly 2 stops"));
+    }
+
+    #[test]
+    fn test_background_image() {
+        let styles = parse_style_map(
+            r#"{ "hero": { "background": { "image": "hero.png" } },
+                 "logo": { "background": { "image": "logo.svg", "fit": "contain" } } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            styles["hero"].background,
+            Some(StyleBackground::Image {
+                path: "hero.png".into(),
+                fit: StyleObjectFit::Cover
+            })
+        );
+        let err =
+            parse_style_map(r#"{ "a": { "background": { "image": "a.png", "fit": "tile" } } }"#)
+                .unwrap_err();
+        assert!(format!("{:#}", err).contains("unknown variant `tile`"));
+
+        // Image paths are relative to the styles file.
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        std::fs::write(dir.join("hero.png"), b"png").unwrap();
+        let style_data = MyStyleData::new(styles).with_source(dir.join("styles.pjson"));
+        // Read with the styles, not when resolved.
+        std::fs::remove_file(dir.join("hero.png")).unwrap();
+        let bytes = style_data.resolve("hero", None, |resolved| {
+            resolved
+                .background_image
+                .as_ref()
+                .map(|image| image.image.bytes.clone())
+        });
+        assert_eq!(bytes, Some(b"png".to_vec()));
+        let mut images = style_data.background_images();
+        images.sort();
+        assert_eq!(images, [dir.join("hero.png"), dir.join("logo.svg")]);
+    }
+
+    #[test]
+    fn test_transition_on_reload() {
+        let load = |json: &str| MyStyleData::new(parse_style_map(json).unwrap());
+        let old = load(
+            r#"{ "panel": { "height": 100, "bg_color": "black", "margin": 0,
+                            "transition": { "duration_ms": 200 } } }"#,
+        );
+        assert!(old.resolve_transition("p", "panel", None).is_none());
+
+        let new = load(
+            r#"{ "panel": { "height": 300, "bg_color": "white", "margin_top": 10,
+                            "transition": { "duration_ms": 200, "easing": "linear",
+                                            "properties": ["height", "margin"] } } }"#,
+        );
+        new.keep_state_of(&old);
+        assert!(new.resolve_transition("p", "panel", None).is_some());
+    }
+
+    struct TransitionView(Rc<MyStyleData>);
+
+    impl Render for TransitionView {
+        fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+            let button = div().id("b").size(px(50.));
+            let button = button.stateful_class_transition("b", "btn", false, &self.0);
+            div().size_full().child(button)
+        }
+    }
+
+    #[gpui::test]
+    fn test_state_transition(cx: &mut TestAppContext) {
+        let styles = parse_style_map(
+            r#"{ "btn": { "hover": { "opacity": 0.8 }, "active": { "opacity": 0.5 },
+                          "transition": { "duration_ms": 200 } } }"#,
+        )
+        .unwrap();
+        let style_data = Rc::new(MyStyleData::new(styles));
+        let btn = &style_data.style_map["btn"];
+        let states = || style_data.transitions.pointer_rules("b", btn, false).len();
+
+        let (_, cx) = cx.add_window_view(|_, _| TransitionView(style_data.clone()));
+        let inside = point(px(10.), px(10.));
+        cx.simulate_mouse_move(inside, None, Modifiers::default());
+        assert_eq!(states(), 1);
+        cx.simulate_mouse_down(inside, MouseButton::Left, Modifiers::default());
+        assert_eq!(states(), 2);
+        cx.simulate_mouse_up(inside, MouseButton::Left, Modifiers::default());
+        cx.simulate_mouse_move(point(px(200.), px(200.)), None, Modifiers::default());
+        assert_eq!(states(), 0);
+    }
+
+    // Paints a div and records whether gpui computes a background for it, hover included.
+    struct Probe(Div, Rc<Cell<bool>>);
+
+    impl IntoElement for Probe {
+        type Element = Self;
+
+        fn into_element(self) -> Self {
+            self
+        }
+    }
+
+    impl Element for Probe {
+        type RequestLayoutState = DivFrameState;
+        type PrepaintState = Option<Hitbox>;
+
+        fn id(&self) -> Option<ElementId> {
+            Element::id(&self.0)
+        }
+
+        fn source_location(&self) -> Option<&'static std::panic::Location<'static>> {
+            Element::source_location(&self.0)
+        }
+
+        fn request_layout(
+            &mut self,
+            id: Option<&GlobalElementId>,
+            inspector_id: Option<&InspectorElementId>,
+            window: &mut Window,
+            cx: &mut App,
+        ) -> (LayoutId, DivFrameState) {
+            self.0.request_layout(id, inspector_id, window, cx)
+        }
+
+        fn prepaint(
+            &mut self,
+            id: Option<&GlobalElementId>,
+            inspector_id: Option<&InspectorElementId>,
+            bounds: Bounds<Pixels>,
+            request_layout: &mut DivFrameState,
+            window: &mut Window,
+            cx: &mut App,
+        ) -> Option<Hitbox> {
+            self.0
+                .prepaint(id, inspector_id, bounds, request_layout, window, cx)
+        }
+
+        fn paint(
+            &mut self,
+            id: Option<&GlobalElementId>,
+            inspector_id: Option<&InspectorElementId>,
+            bounds: Bounds<Pixels>,
+            request_layout: &mut DivFrameState,
+            hitbox: &mut Option<Hitbox>,
+            window: &mut Window,
+            cx: &mut App,
+        ) {
+            let style = self
+                .0
+                .interactivity()
+                .compute_style(id, hitbox.as_ref(), window, cx);
+            self.1.set(style.background.is_some());
+            self.0
+                .paint(id, inspector_id, bounds, request_layout, hitbox, window, cx);
+        }
+    }
+
+    struct ProbeView(Rc<MyStyleData>, Rc<Cell<bool>>);
+
+    impl Render for ProbeView {
+        fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+            let probe = div().size(px(50.)).interactive_class("btn", false, &self.0);
+            div().size_full().child(Probe(probe, self.1.clone()))
+        }
+    }
+
+    #[gpui::test]
+    fn test_hover_reaches_div(cx: &mut TestAppContext) {
+        let styles =
+            parse_style_map(r##"{ "btn": { "hover": { "bg_color": "#ff0000" } } }"##).unwrap();
+        let style_data = Rc::new(MyStyleData::new(styles));
+        let background = Rc::new(Cell::new(false));
+
+        // Any `Styled` element takes classes, hover sub-rules only apply where gpui has them.
+        let _ = StyleRefinement::default().class("btn", &style_data);
+
+        let (_, cx) = cx.add_window_view(|_, _| ProbeView(style_data.clone(), background.clone()));
+        cx.simulate_mouse_move(point(px(200.), px(200.)), None, Modifiers::default());
+        assert!(!background.get());
+        cx.simulate_mouse_move(point(px(10.), px(10.)), None, Modifiers::default());
+        assert!(background.get());
+    }
+
+    #[test]
+    fn test_selectors() {
+        let (styles, selectors) = parse_style_sheet(
+            r##"{
+                "primary": { "width": 2, "height": 2, "gap": 5 },
+                "selectors": {
+                    "#ok": { "width": 4 },
+                    ".bar button": { "height": 3, "opacity": 0.5 },
+                    "button": { "width": 1, "height": 1, "opacity": 1, "extends": "primary" },
+                    "label": { "width": 9 }
+                }
+            }"##,
+        )
+        .unwrap();
+        let data = MyStyleData::new(styles).with_selectors(selectors);
+        let bar = SelectorTarget {
+            element: "div",
+            id: None,
+            classes: "bar",
+        };
+        let button = SelectorTarget {
+            element: "button",
+            id: Some("ok"),
+            classes: "primary",
+        };
+
+        // `#ok` > `.bar button` > `primary` > `button`.
+        let resolved = data.resolve_element(&[bar, button], None, |r| r.rule.clone());
+        assert_eq!(resolved.width, Some(StyleLength::Px(4.0)));
+        assert_eq!(resolved.height, Some(StyleLength::Px(3.0)));
+        assert_eq!(resolved.opacity, Some(0.5));
+
+        let resolved = data.resolve_element(
+            &[SelectorTarget {
+                id: None,
+                classes: "",
+                ..button
+            }],
+            None,
+            |r| r.rule.clone(),
+        );
+        // A plain `button` gets the selector rule, which extends `primary`.
+        assert_eq!(resolved.width, Some(StyleLength::Px(1.0)));
+        assert_eq!(resolved.gap, Some(StyleLength::Px(5.0)));
+        assert_eq!(resolved.opacity, Some(1.0));
+
+        assert!(parse_style_sheet(r#"{ "selectors": { "buton": {} } }"#).is_err());
+        assert!(parse_style_sheet(r##"{ "selectors": { "#a#b": {} } }"##).is_err());
+    }
+
+    #[test]
+    fn test_utility_classes() {
+        let data = MyStyleData::new(
+            parse_style_map(r#"{ "p-4": { "padding": 3 }, "card": { "gap": 1 } }"#).unwrap(),
+        );
+        let rule = data.resolve(
+            "card flex flex-col p-4 px-2 gap-2 bg-[#fff] rounded-lg hover:bg-[red] md:w-1/2 bg-[#zzz] nonsense",
+            None,
+            |r| r.rule.clone(),
+        );
+
+        assert_eq!(rule.display, Some(StyleDisplay::Flex));
+        // Classes of the styles file take precedence over utilities.
+        assert_eq!(rule.padding, Some(StyleLength::Px(3.0)));
+        assert_eq!(rule.padding_left, Some(StyleLength::Rems(0.5)));
+        assert_eq!(rule.gap, Some(StyleLength::Rems(0.5)));
+        assert_eq!(rule.bg_color, Some(StyleColor::Rgba(rgb(0xffffff))));
+        assert_eq!(rule.rounded, Some(StyleRadius::Preset(RadiusPreset::Lg)));
+        assert_eq!(
+            rule.hover.unwrap().bg_color,
+            Some(StyleColor::Rgba(rgb(0xff0000)))
+        );
+        // `md:` only applies from 768px on.
+        assert_eq!(rule.width, None);
+
+        // Semantic class names that start like a utility are not utilities.
+        for class in ["p-header", "text-muted", "shadow-card", "hover:font-title"] {
+            assert!(parse_utility_class(class).is_none(), "{}", class);
+        }
+        assert!(matches!(parse_utility_class("text-[huge]"), Some(Err(_))));
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let styles = parse_style_map(
+            r##"{
+                "card": {
+                    "display": "flex", "width": "50%", "height": "2rem", "padding": 4,
+                    "min_width": "1/3", "max_width": "full",
+                    "bg_color": "#ff000080", "text_color": "theme.primary", "font_size": "1.5rem",
+                    "border_color": "hsl(210, 50%, 40%)",
+                    "font_weight": 700, "rounded": "lg", "shadow": [{ "offset_y": 2, "blur": 4 }],
+                    "col_span": "full", "cursor": "pointer",
+                    "background": { "linear_gradient": { "angle": 90, "stops": [
+                        { "color": "red", "position": 0 }, { "color": "blue", "position": 1 }
+                    ] } },
+                    "transition": { "duration_ms": 200, "properties": ["bg_color"] },
+                    "hover": { "opacity": 0.1 },
+                    "breakpoints": { "md": { "rounded": 3 } }
+                }
+            }"##,
+        )
+        .unwrap();
+        let card = &styles["card"];
+
+        let value = card.to_json();
+        assert_eq!(value["bg_color"], "#ff000080");
+        assert_eq!(value["text_color"], "theme.primary");
+        assert_eq!(value["border_color"], "hsl(210, 50%, 40%)");
+        assert_eq!(value["width"], "50%");
+        assert_eq!(value["min_width"], "1/3");
+        assert_eq!(value["max_width"], "full");
+        assert_eq!(
+            value["background"]["linear_gradient"]["stops"][0]["color"],
+            "red"
+        );
+        assert_eq!(value["hover"]["opacity"], 0.1);
+        assert!(value.get("margin").is_none());
+        assert_eq!(serde_json::from_value::<StyleRule>(value).unwrap(), *card);
+
+        assert_eq!(StyleRule::default().to_json(), serde_json::json!({}));
     }
 }
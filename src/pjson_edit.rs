@@ -0,0 +1,427 @@
+use anyhow::{bail, Context, Result};
+
+// Edits of a pjson document (json with comments, unquoted keys and trailing
+// commas) that keep everything else as written: key order, comments and
+// formatting. Used to write values changed in the style editor back to the
+// styles file.
+
+// Set the value at `path` of the root object, e.g. `["btn1", "hover", "bg_color"]`.
+// Missing objects along the path are created, new keys are appended.
+pub fn set_value(source: &str, path: &[&str], value: &serde_json::Value) -> Result<String> {
+    let (key, parents) = path.split_last().context("empty path")?;
+    let root = root_object(source)?;
+
+    let mut object = root;
+    for (depth, parent) in parents.iter().enumerate() {
+        match object.members.iter().find(|m| m.key == *parent) {
+            Some(member) if source[member.value.0..].starts_with('{') => {
+                object = Scanner::new(source, member.value.0).object()?;
+            }
+            Some(_) => bail!("`{}` is not an object", path[..=depth].join(".")),
+            None => {
+                // Create the rest of the path as nested objects.
+                let mut nested = value.clone();
+                for k in path[depth + 1..].iter().rev() {
+                    nested = serde_json::json!({ *k: nested });
+                }
+                return Ok(insert_member(source, &object, parent, &nested));
+            }
+        }
+    }
+
+    Ok(match object.members.iter().find(|m| m.key == *key) {
+        Some(member) => format!(
+            "{}{}{}",
+            &source[..member.value.0],
+            format_value(value),
+            &source[member.value.1..]
+        ),
+        None => insert_member(source, &object, key, value),
+    })
+}
+
+// Remove the key at `path`, with its comma. Does nothing if it is missing.
+pub fn remove_value(source: &str, path: &[&str]) -> Result<String> {
+    let (key, parents) = path.split_last().context("empty path")?;
+
+    let mut object = root_object(source)?;
+    for parent in parents {
+        match object.members.iter().find(|m| m.key == *parent) {
+            Some(member) if source[member.value.0..].starts_with('{') => {
+                object = Scanner::new(source, member.value.0).object()?;
+            }
+            _ => return Ok(source.to_string()),
+        }
+    }
+    let Some(member) = object.members.iter().find(|m| m.key == *key) else {
+        return Ok(source.to_string());
+    };
+
+    // Take the indentation along when the member starts its line.
+    let line_start = source[..member.start].rfind('\n').map_or(0, |i| i + 1);
+    let start = if source[line_start..member.start].trim().is_empty() {
+        line_start
+    } else {
+        member.start
+    };
+    let mut end = member.end;
+    let rest = &source[end..];
+    let line_end = rest.find('\n').map_or(rest.len(), |i| i + 1);
+    if start == line_start && rest[..line_end].trim().is_empty() {
+        end += line_end;
+    }
+    Ok(format!("{}{}", &source[..start], &source[end..]))
+}
+
+// Keys given more than once in the same object, as paths such as
+// `["btn1", "width"]`. Json parsers silently keep the last value.
+pub fn duplicate_keys(source: &str) -> Result<Vec<Vec<String>>> {
+    fn walk(
+        source: &str,
+        object: &ObjectSpan,
+        path: &mut Vec<String>,
+        duplicates: &mut Vec<Vec<String>>,
+    ) -> Result<()> {
+        for (i, member) in object.members.iter().enumerate() {
+            if object.members[..i].iter().any(|m| m.key == member.key) {
+                let mut duplicate = path.clone();
+                duplicate.push(member.key.clone());
+                duplicates.push(duplicate);
+            }
+            if source[member.value.0..].starts_with('{') {
+                path.push(member.key.clone());
+                walk(
+                    source,
+                    &Scanner::new(source, member.value.0).object()?,
+                    path,
+                    duplicates,
+                )?;
+                path.pop();
+            }
+        }
+        Ok(())
+    }
+
+    let mut duplicates = Vec::new();
+    walk(
+        source,
+        &root_object(source)?,
+        &mut Vec::new(),
+        &mut duplicates,
+    )?;
+    Ok(duplicates)
+}
+
+// `{ key: value }` as written in the styles files: unquoted keys where possible.
+pub fn format_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Object(map) if map.is_empty() => "{}".to_string(),
+        serde_json::Value::Object(map) => {
+            let members: Vec<String> = map
+                .iter()
+                .map(|(k, v)| format!("{}: {}", format_key(k), format_value(v)))
+                .collect();
+            format!("{{ {} }}", members.join(", "))
+        }
+        serde_json::Value::Array(list) => {
+            let items: Vec<String> = list.iter().map(format_value).collect();
+            format!("[{}]", items.join(", "))
+        }
+        other => other.to_string(),
+    }
+}
+
+fn format_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && !key.starts_with(|c: char| c.is_ascii_digit())
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if bare {
+        key.to_string()
+    } else {
+        serde_json::Value::from(key).to_string()
+    }
+}
+
+fn root_object(source: &str) -> Result<ObjectSpan> {
+    let mut scanner = Scanner::new(source, 0);
+    scanner.skip_trivia();
+    scanner.object()
+}
+
+// Append `key: value` to `object`, on its own line when the object spans lines.
+fn insert_member(
+    source: &str,
+    object: &ObjectSpan,
+    key: &str,
+    value: &serde_json::Value,
+) -> String {
+    let newline = if source.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let member = format!("{}: {}", format_key(key), format_value(value));
+
+    let Some(last) = object.members.last() else {
+        return format!(
+            "{}{{ {} }}{}",
+            &source[..object.open],
+            member,
+            &source[object.close + 1..]
+        );
+    };
+
+    if !source[object.open..object.close].contains('\n') {
+        let sep = if last.has_comma { " " } else { ", " };
+        return format!(
+            "{}{}{}{}",
+            &source[..last.end],
+            sep,
+            member,
+            &source[last.end..]
+        );
+    }
+
+    let line_start = source[..last.start].rfind('\n').map_or(0, |i| i + 1);
+    let indent: String = source[line_start..last.start]
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect();
+    // After the rest of the last member's line, so that its comment stays with it.
+    let rest = &source[last.end..object.close];
+    let at = match rest.find('\n') {
+        Some(i) if source[..last.end + i].ends_with('\r') => last.end + i - 1,
+        Some(i) => last.end + i,
+        None => last.end,
+    };
+
+    let mut edited = String::with_capacity(source.len() + member.len() + 8);
+    if last.has_comma {
+        edited.push_str(&source[..at]);
+    } else {
+        edited.push_str(&source[..last.value.1]);
+        edited.push(',');
+        edited.push_str(&source[last.value.1..at]);
+    }
+    edited.push_str(newline);
+    edited.push_str(&indent);
+    edited.push_str(&member);
+    edited.push(',');
+    edited.push_str(&source[at..]);
+    edited
+}
+
+#[derive(Debug)]
+struct ObjectSpan {
+    open: usize,
+    close: usize,
+    members: Vec<Member>,
+}
+
+#[derive(Debug)]
+struct Member {
+    key: String,
+    // From the key to the end of the value, or of its comma.
+    start: usize,
+    end: usize,
+    value: (usize, usize),
+    has_comma: bool,
+}
+
+struct Scanner<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(source: &'a str, pos: usize) -> Self {
+        Scanner { source, pos }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    // Whitespace and comments.
+    fn skip_trivia(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if let Some(comment) = trimmed.strip_prefix("/*") {
+                self.pos += 2 + comment.find("*/").map_or(comment.len(), |i| i + 2);
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<ObjectSpan> {
+        if self.peek() != Some('{') {
+            bail!("expected an object at byte {}", self.pos);
+        }
+        let open = self.pos;
+        self.pos += 1;
+        let mut members = Vec::new();
+        loop {
+            self.skip_trivia();
+            match self.peek() {
+                Some('}') => {
+                    return Ok(ObjectSpan {
+                        open,
+                        close: self.pos,
+                        members,
+                    })
+                }
+                Some(_) => {}
+                None => bail!("unterminated object starting at byte {}", open),
+            }
+
+            let start = self.pos;
+            let key = self.key()?;
+            self.skip_trivia();
+            if self.peek() != Some(':') {
+                bail!("expected `:` after `{}` at byte {}", key, self.pos);
+            }
+            self.pos += 1;
+            self.skip_trivia();
+            let value_start = self.pos;
+            self.skip_value()?;
+            let value = (value_start, self.pos);
+
+            let mut end = self.pos;
+            self.skip_trivia();
+            let has_comma = self.peek() == Some(',');
+            if has_comma {
+                self.pos += 1;
+                end = self.pos;
+            }
+            members.push(Member {
+                key,
+                start,
+                end,
+                value,
+                has_comma,
+            });
+        }
+    }
+
+    fn key(&mut self) -> Result<String> {
+        match self.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                let start = self.pos;
+                self.skip_string(quote)?;
+                let raw = &self.source[start + 1..self.pos - 1];
+                Ok(serde_json::from_str(&format!("\"{}\"", raw)).unwrap_or(raw.to_string()))
+            }
+            _ => {
+                let rest = self.rest();
+                let len = rest
+                    .find(|c: char| c == ':' || c.is_whitespace())
+                    .unwrap_or(rest.len());
+                if len == 0 {
+                    bail!("expected a key at byte {}", self.pos);
+                }
+                self.pos += len;
+                Ok(rest[..len].to_string())
+            }
+        }
+    }
+
+    fn skip_string(&mut self, quote: char) -> Result<()> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut escaped = false;
+        for (i, c) in self.rest().char_indices() {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == quote {
+                self.pos += i + 1;
+                return Ok(());
+            }
+        }
+        bail!("unterminated string starting at byte {}", start)
+    }
+
+    fn skip_value(&mut self) -> Result<()> {
+        match self.peek() {
+            Some(quote @ ('"' | '\'')) => self.skip_string(quote),
+            Some(open @ ('{' | '[')) => {
+                let close = if open == '{' { '}' } else { ']' };
+                let start = self.pos;
+                self.pos += 1;
+                loop {
+                    self.skip_trivia();
+                    match self.peek() {
+                        Some(c) if c == close => {
+                            self.pos += 1;
+                            return Ok(());
+                        }
+                        Some(',' | ':') => self.pos += 1,
+                        Some('}' | ']') | None => {
+                            bail!("unbalanced brackets starting at byte {}", start)
+                        }
+                        Some('"' | '\'' | '{' | '[') => self.skip_value()?,
+                        Some(_) => {
+                            // An unquoted key or a literal.
+                            let rest = self.rest();
+                            let len = rest
+                                .find(|c: char| ",:{}[]\"'".contains(c) || c.is_whitespace())
+                                .unwrap_or(rest.len());
+                            self.pos += len.max(1);
+                        }
+                    }
+                }
+            }
+            Some(_) => {
+                let rest = self.rest();
+                let len = rest
+                    .find(|c: char| ",}]".contains(c) || c.is_whitespace())
+                    .unwrap_or(rest.len());
+                if len == 0 {
+                    bail!("expected a value at byte {}", self.pos);
+                }
+                self.pos += len;
+                Ok(())
+            }
+            None => bail!("expected a value at the end of the document"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{remove_value, set_value};
+    use serde_json::json;
+
+    #[test]
+    fn test_edit_keeps_comments_and_order() {
+        let source = "{\r\n  // buttons\r\n  btn1: {\r\n    width: 10, // wide\r\n    height: 5\r\n  },\r\n  box: { width: 1 },\r\n}\r\n";
+
+        let edited = set_value(source, &["btn1", "width"], &json!(20)).unwrap();
+        assert!(edited.contains("    width: 20, // wide\r\n"));
+
+        let edited = set_value(&edited, &["btn1", "bg_color"], &json!("#fff")).unwrap();
+        assert!(edited.contains("    height: 5,\r\n    bg_color: \"#fff\",\r\n  },"));
+
+        let edited = set_value(&edited, &["box", "height"], &json!(2)).unwrap();
+        assert!(edited.contains("  box: { width: 1, height: 2 },\r\n"));
+
+        let edited = set_value(&edited, &["new", "hover", "bg_color"], &json!("red")).unwrap();
+        assert!(edited.ends_with("  new: { hover: { bg_color: \"red\" } },\r\n}\r\n"));
+
+        let edited = remove_value(&edited, &["btn1", "height"]).unwrap();
+        assert_eq!(
+            edited,
+            "{\r\n  // buttons\r\n  btn1: {\r\n    width: 20, // wide\r\n    bg_color: \"#fff\",\r\n  },\r\n  box: { width: 1, height: 2 },\r\n  new: { hover: { bg_color: \"red\" } },\r\n}\r\n"
+        );
+    }
+}
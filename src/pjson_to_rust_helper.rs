@@ -111,6 +111,9 @@ fn write_button(
     if let Some(label) = obj.get("label").and_then(|v| v.as_str()) {
         write!(output, ".label(\"{}\")", label.escape_default()).unwrap();
     }
+    if let Some(true) = obj.get("disabled").and_then(|v| v.as_bool()) {
+        write!(output, ".disabled(true)").unwrap();
+    }
     write_common_attrs(obj, output, inner_indent);
     if let Some(on_click) = obj.get("on_click").and_then(|v| v.as_str()) {
         write!(output, r#".on_click(Self::{}(cx))"#, on_click).unwrap();
@@ -171,7 +174,23 @@ fn write_common_attrs(
     _indent: &str,
 ) {
     if let Some(class) = obj.get("class").and_then(|v| v.as_str()) {
-        write!(output, r#".class("{}", sd)"#, class).unwrap();
+        // Divs and buttons have gpui's hover and focus styles; only buttons
+        // take `disabled`, see `my_layout_data::LAYOUT_ELEMENTS`.
+        let etype = obj.get("type").and_then(|v| v.as_str()).unwrap_or("div");
+        let disabled =
+            etype == "button" && obj.get("disabled").and_then(|v| v.as_bool()) == Some(true);
+        if etype == "div" || etype == "button" {
+            write!(
+                output,
+                r#".interactive_class("{}", {}, sd)"#,
+                class, disabled
+            )
+            .unwrap();
+        } else if disabled {
+            write!(output, r#".class_disabled("{}", true, sd)"#, class).unwrap();
+        } else {
+            write!(output, r#".class("{}", sd)"#, class).unwrap();
+        }
     }
 
     if let Some(style) = obj.get("style") {
@@ -0,0 +1,1303 @@
+use anyhow::{bail, Result};
+use serde_json::{json, Map, Value};
+use std::fmt;
+
+use crate::css_color::{hex_color, parse_css_color};
+use crate::my_style_data::{MediaQuery, StyleLength, StyleMap, StyleRadius, StyleRule, StyleTheme};
+use crate::style_selector::SelectorRule;
+
+// Conversion between a `StyleMap` and CSS class rules, for prototypes made in
+// HTML/CSS. The rules of `selectors` are exported too, but only class rules are
+// imported. `.class`, `.class:hover` (`:active`, `:focus`, `:disabled`) and
+// `@media` blocks of `min-width`, `max-width`, `min-height`, `max-height` and
+// `prefers-color-scheme` conditions are supported. `"theme.primary"` colors are
+// written as `var(--theme-primary)`.
+//
+// Whatever has no equivalent on the other side is listed in the returned report.
+
+// A declaration (or a whole rule) that could not be converted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unsupported {
+    // `.btn1:hover`, or the at-rule.
+    pub selector: String,
+    // `transform: rotate(4deg)`, or empty for a whole rule.
+    pub declaration: String,
+    pub reason: String,
+}
+
+impl fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.declaration.is_empty() {
+            write!(f, "{}: {}", self.selector, self.reason)
+        } else {
+            write!(
+                f,
+                "{} {{ {} }}: {}",
+                self.selector, self.declaration, self.reason
+            )
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CssValue {
+    // A number of pixels, `"1.5rem"`, `"50%"` or `"auto"`.
+    Length,
+    // A number of pixels only.
+    Pixels,
+    // A unitless number, or a keyword such as `bold`.
+    Number,
+    Color,
+    Keyword,
+}
+
+// `StyleRule` properties with a CSS property of the same meaning.
+const PROPERTIES: &[(&str, &str, CssValue)] = &[
+    ("width", "width", CssValue::Length),
+    ("height", "height", CssValue::Length),
+    ("min_width", "min-width", CssValue::Length),
+    ("min_height", "min-height", CssValue::Length),
+    ("max_width", "max-width", CssValue::Length),
+    ("max_height", "max-height", CssValue::Length),
+    ("flex_basis", "flex-basis", CssValue::Length),
+    ("inset", "inset", CssValue::Length),
+    ("top", "top", CssValue::Length),
+    ("right", "right", CssValue::Length),
+    ("bottom", "bottom", CssValue::Length),
+    ("left", "left", CssValue::Length),
+    ("margin", "margin", CssValue::Length),
+    ("margin_top", "margin-top", CssValue::Length),
+    ("margin_right", "margin-right", CssValue::Length),
+    ("margin_bottom", "margin-bottom", CssValue::Length),
+    ("margin_left", "margin-left", CssValue::Length),
+    ("padding", "padding", CssValue::Length),
+    ("padding_top", "padding-top", CssValue::Length),
+    ("padding_right", "padding-right", CssValue::Length),
+    ("padding_bottom", "padding-bottom", CssValue::Length),
+    ("padding_left", "padding-left", CssValue::Length),
+    ("gap", "gap", CssValue::Length),
+    ("gap_x", "column-gap", CssValue::Length),
+    ("gap_y", "row-gap", CssValue::Length),
+    ("line_height", "line-height", CssValue::Length),
+    ("font_size", "font-size", CssValue::Length),
+    ("font_weight", "font-weight", CssValue::Number),
+    ("font_family", "font-family", CssValue::Keyword),
+    ("bg_color", "background-color", CssValue::Color),
+    ("text_color", "color", CssValue::Color),
+    ("border_color", "border-color", CssValue::Color),
+    ("border_style", "border-style", CssValue::Keyword),
+    ("border_width", "border-width", CssValue::Pixels),
+    ("border_top", "border-top-width", CssValue::Pixels),
+    ("border_right", "border-right-width", CssValue::Pixels),
+    ("border_bottom", "border-bottom-width", CssValue::Pixels),
+    ("border_left", "border-left-width", CssValue::Pixels),
+    ("rounded", "border-radius", CssValue::Pixels),
+    ("rounded_tl", "border-top-left-radius", CssValue::Pixels),
+    ("rounded_tr", "border-top-right-radius", CssValue::Pixels),
+    ("rounded_bl", "border-bottom-left-radius", CssValue::Pixels),
+    ("rounded_br", "border-bottom-right-radius", CssValue::Pixels),
+    ("text_align", "text-align", CssValue::Keyword),
+    ("whitespace", "white-space", CssValue::Keyword),
+    ("display", "display", CssValue::Keyword),
+    ("justify_content", "justify-content", CssValue::Keyword),
+    ("align_items", "align-items", CssValue::Keyword),
+    ("align_self", "align-self", CssValue::Keyword),
+    ("flex_direction", "flex-direction", CssValue::Keyword),
+    ("flex_grow", "flex-grow", CssValue::Number),
+    ("flex_shrink", "flex-shrink", CssValue::Number),
+    ("position", "position", CssValue::Keyword),
+    ("opacity", "opacity", CssValue::Number),
+    ("cursor", "cursor", CssValue::Keyword),
+    ("overflow_x", "overflow-x", CssValue::Keyword),
+    ("overflow_y", "overflow-y", CssValue::Keyword),
+    ("line_clamp", "-webkit-line-clamp", CssValue::Number),
+    ("col_start", "grid-column-start", CssValue::Number),
+    ("col_end", "grid-column-end", CssValue::Number),
+    ("row_start", "grid-row-start", CssValue::Number),
+    ("row_end", "grid-row-end", CssValue::Number),
+];
+
+// `TransitionProperty` names and the CSS properties they animate.
+const TRANSITION_PROPERTIES: &[(&str, &str)] = &[
+    ("all", "all"),
+    ("bg_color", "background-color"),
+    ("text_color", "color"),
+    ("border_color", "border-color"),
+    ("opacity", "opacity"),
+    ("width", "width"),
+    ("height", "height"),
+    ("margin", "margin"),
+    ("padding", "padding"),
+    ("gap", "gap"),
+];
+
+const STATES: &[&str] = &["hover", "active", "focus", "disabled"];
+
+// Write the class rules of `styles` as CSS, sorted by class name.
+pub fn style_map_to_css(styles: &StyleMap) -> (String, Vec<Unsupported>) {
+    style_sheet_to_css(styles, &[])
+}
+
+// Write the class rules of `styles` as CSS, sorted by class name, followed by
+// the rules of `selectors` in their order.
+pub fn style_sheet_to_css(
+    styles: &StyleMap,
+    selectors: &[SelectorRule],
+) -> (String, Vec<Unsupported>) {
+    let mut exporter = Exporter::default();
+    let mut classes: Vec<&String> = styles.keys().collect();
+    classes.sort();
+    for class in classes {
+        exporter.rule(None, &format!(".{}", class), &styles[class]);
+    }
+    for selector_rule in selectors {
+        exporter.rule(
+            None,
+            &selector_rule.selector.to_string(),
+            &selector_rule.rule,
+        );
+    }
+
+    let mut css = exporter.css;
+    for (condition, rules) in exporter.media {
+        css.push_str(&format!("@media {} {{\n{}}}\n\n", condition, rules));
+    }
+    (css.trim_end().to_string() + "\n", exporter.report)
+}
+
+// Read the class rules of a stylesheet. Only malformed CSS is an error;
+// unsupported selectors, at-rules, properties and values are reported.
+pub fn css_to_style_map(css: &str) -> Result<(StyleMap, Vec<Unsupported>)> {
+    let mut importer = Importer::default();
+    for item in items(&strip_comments(css))? {
+        importer.item(None, item);
+    }
+    Ok((importer.styles, importer.report))
+}
+
+// `styles` as the content of a styles file, with the `@media` sub-rules in
+// top-level `@media(...)` blocks.
+pub fn style_map_to_json(styles: &StyleMap) -> Value {
+    let mut classes: Vec<&String> = styles.keys().collect();
+    classes.sort();
+
+    let mut root = Map::new();
+    let mut media = Map::new();
+    for class in classes {
+        let rule = &styles[class];
+        root.insert(class.clone(), rule.to_json());
+        for (query, sub_rule) in &rule.media {
+            let block = media
+                .entry(media_key(query))
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Value::Object(block) = block {
+                block.insert(class.clone(), sub_rule.to_json());
+            }
+        }
+    }
+    root.extend(media);
+    Value::Object(root)
+}
+
+fn media_key(query: &MediaQuery) -> String {
+    let mut conditions = Vec::new();
+    for (feature, value) in [
+        ("min_width", query.min_width),
+        ("max_width", query.max_width),
+        ("min_height", query.min_height),
+        ("max_height", query.max_height),
+    ] {
+        if let Some(value) = value {
+            conditions.push(format!("({}: {})", feature, value));
+        }
+    }
+    if let Some(theme) = query.theme {
+        conditions.push(format!("(theme: {})", theme_name(theme)));
+    }
+    format!("@media{}", conditions.join(" and "))
+}
+
+fn theme_name(theme: StyleTheme) -> &'static str {
+    match theme {
+        StyleTheme::Light => "light",
+        StyleTheme::Dark => "dark",
+    }
+}
+
+#[derive(Default)]
+struct Exporter {
+    css: String,
+    // Rules by `@media` condition, in order of appearance.
+    media: Vec<(String, String)>,
+    report: Vec<Unsupported>,
+}
+
+impl Exporter {
+    fn rule(&mut self, condition: Option<&str>, selector: &str, rule: &StyleRule) {
+        let declarations = self.declarations(selector, rule);
+        if !declarations.is_empty() {
+            let (out, indent) = match condition {
+                None => (&mut self.css, ""),
+                Some(condition) => {
+                    let i = match self.media.iter().position(|(c, _)| c == condition) {
+                        Some(i) => i,
+                        None => {
+                            self.media.push((condition.to_string(), String::new()));
+                            self.media.len() - 1
+                        }
+                    };
+                    (&mut self.media[i].1, "  ")
+                }
+            };
+            out.push_str(&format!("{}{} {{\n", indent, selector));
+            for (property, value) in declarations {
+                out.push_str(&format!("{}  {}: {};\n", indent, property, value));
+            }
+            out.push_str(&format!("{}}}\n\n", indent));
+        }
+
+        let states = [&rule.hover, &rule.active, &rule.focus, &rule.disabled];
+        for (state, sub_rule) in STATES.iter().zip(states) {
+            if let Some(sub_rule) = sub_rule {
+                self.rule(condition, &format!("{}:{}", selector, state), sub_rule);
+            }
+        }
+
+        let breakpoints = rule.breakpoints.iter().flatten();
+        let breakpoints =
+            breakpoints.map(|(breakpoint, sub_rule)| (breakpoint.query(), &**sub_rule));
+        let media = rule
+            .media
+            .iter()
+            .map(|(query, sub_rule)| (*query, sub_rule));
+        for (query, sub_rule) in breakpoints.chain(media).collect::<Vec<_>>() {
+            let inner = css_media(&query);
+            let condition = match condition {
+                Some(outer) => format!("{} and {}", outer, inner),
+                None => inner,
+            };
+            self.rule(Some(&condition), selector, sub_rule);
+        }
+    }
+
+    fn declarations(&mut self, selector: &str, rule: &StyleRule) -> Vec<(&'static str, String)> {
+        let Value::Object(properties) = rule.to_json() else {
+            return Vec::new();
+        };
+
+        let mut declarations = Vec::new();
+        let mut decorations = None;
+        for (key, value) in &properties {
+            let mut unsupported = |reason: &str| {
+                self.report.push(Unsupported {
+                    selector: selector.to_string(),
+                    declaration: format!("{}: {}", key, value),
+                    reason: reason.to_string(),
+                })
+            };
+            match key.as_str() {
+                "hover" | "active" | "focus" | "disabled" | "breakpoints" => {}
+                "size_full" => {
+                    if value == true {
+                        declarations.push(("width", "100%".to_string()));
+                        declarations.push(("height", "100%".to_string()));
+                    }
+                }
+                "italic" => {
+                    let style = if value == true { "italic" } else { "normal" };
+                    declarations.push(("font-style", style.to_string()));
+                }
+                "underline" | "line_through" => {
+                    let decorations = decorations.get_or_insert_with(Vec::new);
+                    if value == true {
+                        decorations.push(key.replace('_', "-"));
+                    }
+                }
+                "visible" => {
+                    let visibility = if value == true { "visible" } else { "hidden" };
+                    declarations.push(("visibility", visibility.to_string()));
+                }
+                "text_ellipsis" => {
+                    let overflow = if value == true { "ellipsis" } else { "clip" };
+                    declarations.push(("text-overflow", overflow.to_string()));
+                }
+                "background" => declarations.push(("background", css_background(value))),
+                "shadow" => match value {
+                    Value::Array(shadows) => {
+                        let shadows: Vec<String> = shadows.iter().map(css_shadow).collect();
+                        declarations.push(("box-shadow", shadows.join(", ")));
+                    }
+                    Value::String(preset) if preset == "none" => {
+                        declarations.push(("box-shadow", "none".to_string()))
+                    }
+                    _ => unsupported("gpui's shadow presets have no CSS equivalent"),
+                },
+                "grid_cols" | "grid_rows" => {
+                    let property = if key == "grid_cols" {
+                        "grid-template-columns"
+                    } else {
+                        "grid-template-rows"
+                    };
+                    let count = css_number(value);
+                    declarations.push((property, format!("repeat({}, minmax(0, 1fr))", count)));
+                }
+                "col_span" | "row_span" => {
+                    let property = if key == "col_span" {
+                        "grid-column"
+                    } else {
+                        "grid-row"
+                    };
+                    let span = match value {
+                        Value::String(_) => "1 / -1".to_string(),
+                        n => format!("span {} / span {}", css_number(n), css_number(n)),
+                    };
+                    declarations.push((property, span));
+                }
+                "transition" => match css_transition(value) {
+                    Some(transition) => declarations.push(("transition", transition)),
+                    None => unsupported("invalid transition"),
+                },
+                _ => match PROPERTIES.iter().find(|(k, _, _)| k == key) {
+                    Some((_, property, kind)) => {
+                        declarations.push((*property, css_value(key, *kind, value)))
+                    }
+                    None => unsupported("no CSS equivalent"),
+                },
+            }
+        }
+        if let Some(decorations) = decorations {
+            let decoration = if decorations.is_empty() {
+                "none".to_string()
+            } else {
+                decorations.join(" ")
+            };
+            declarations.push(("text-decoration", decoration));
+        }
+        declarations
+    }
+}
+
+fn css_value(key: &str, kind: CssValue, value: &Value) -> String {
+    match (kind, value) {
+        (CssValue::Color, Value::String(color)) => css_color(color),
+        (CssValue::Length | CssValue::Pixels, Value::Number(_)) => {
+            format!("{}px", css_number(value))
+        }
+        // Radius presets.
+        (CssValue::Pixels, Value::String(_)) => {
+            match serde_json::from_value::<StyleRadius>(value.clone()) {
+                Ok(radius) => match gpui::AbsoluteLength::from(radius) {
+                    gpui::AbsoluteLength::Pixels(px) => format!("{}px", f32::from(px)),
+                    gpui::AbsoluteLength::Rems(rems) => format!("{}rem", rems.0),
+                },
+                Err(_) => value.to_string(),
+            }
+        }
+        // CSS has no `"1/3"` or `"full"`.
+        (CssValue::Length, Value::String(s)) => match s.parse::<StyleLength>() {
+            Ok(length @ (StyleLength::Fraction(..) | StyleLength::Full)) => {
+                format!("{}%", length.relative().unwrap_or_default() * 100.0)
+            }
+            _ => s.clone(),
+        },
+        (CssValue::Keyword, Value::String(s)) if key == "font_family" && s.contains(' ') => {
+            format!("\"{}\"", s)
+        }
+        (_, Value::String(s)) => s.clone(),
+        (_, other) => css_number(other),
+    }
+}
+
+// `8` rather than the `8.0` of a serialized `f32`.
+fn css_number(value: &Value) -> String {
+    value.as_f64().map_or(value.to_string(), |n| n.to_string())
+}
+
+fn css_color(color: &str) -> String {
+    match color.strip_prefix("theme.") {
+        Some(name) => format!("var(--theme-{})", name.replace('_', "-")),
+        None => match (color.rsplit_once('/'), parse_css_color(color)) {
+            // The `"red / 0.5"` alpha override is not CSS, unlike `rgb(0 0 0 / 0.5)`.
+            (Some((_, alpha)), Ok(rgba)) if !alpha.contains(')') => hex_color(rgba),
+            _ => color.to_string(),
+        },
+    }
+}
+
+fn css_background(value: &Value) -> String {
+    if let Some(image) = value.get("image").and_then(Value::as_str) {
+        let size = match value.get("fit").and_then(Value::as_str) {
+            Some("fill") => "100% 100%",
+            Some("contain") | Some("scale-down") => "contain",
+            Some("none") => "auto",
+            _ => "cover",
+        };
+        return format!("url(\"{}\") center / {} no-repeat", image, size);
+    }
+    let Some(gradient) = value.get("linear_gradient") else {
+        return value.as_str().map(css_color).unwrap_or_default();
+    };
+    let stops: Vec<String> = gradient["stops"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|stop| {
+            let color = stop["color"].as_str().map(css_color).unwrap_or_default();
+            let position = stop["position"].as_f64().unwrap_or_default();
+            format!("{} {}%", color, position * 100.0)
+        })
+        .collect();
+    format!(
+        "linear-gradient({}deg, {})",
+        css_number(&gradient["angle"]),
+        stops.join(", ")
+    )
+}
+
+fn css_shadow(shadow: &Value) -> String {
+    let length = |key: &str| format!("{}px", shadow[key].as_f64().unwrap_or_default());
+    // The default color of `StyleBoxShadow`.
+    let color = shadow["color"]
+        .as_str()
+        .map_or("#00000040".to_string(), css_color);
+    format!(
+        "{} {} {} {} {}",
+        length("offset_x"),
+        length("offset_y"),
+        length("blur"),
+        length("spread"),
+        color
+    )
+}
+
+fn css_transition(value: &Value) -> Option<String> {
+    let duration = value["duration_ms"].as_u64()?;
+    let easing = value["easing"].as_str()?;
+    let mut properties: Vec<&str> = value["properties"]
+        .as_array()?
+        .iter()
+        .filter_map(|p| {
+            let p = p.as_str()?;
+            TRANSITION_PROPERTIES
+                .iter()
+                .find(|(name, _)| *name == p)
+                .map(|(_, css)| *css)
+        })
+        .collect();
+    if properties.is_empty() {
+        properties.push("all");
+    }
+    let transitions: Vec<String> = properties
+        .iter()
+        .map(|p| format!("{} {}ms {}", p, duration, easing))
+        .collect();
+    Some(transitions.join(", "))
+}
+
+fn css_media(query: &MediaQuery) -> String {
+    let mut conditions = Vec::new();
+    for (feature, value) in [
+        ("min-width", query.min_width),
+        ("max-width", query.max_width),
+        ("min-height", query.min_height),
+        ("max-height", query.max_height),
+    ] {
+        if let Some(value) = value {
+            conditions.push(format!("({}: {}px)", feature, value));
+        }
+    }
+    if let Some(theme) = query.theme {
+        conditions.push(format!("(prefers-color-scheme: {})", theme_name(theme)));
+    }
+    conditions.join(" and ")
+}
+
+// A top-level item of a stylesheet.
+enum Item<'a> {
+    // `@import url(x.css);`
+    Statement(&'a str),
+    // A prelude such as `.btn:hover` or `@media (min-width: 600px)`, and the
+    // content between its braces.
+    Block(&'a str, &'a str),
+}
+
+fn strip_comments(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        rest = match rest[start + 2..].find("*/") {
+            Some(end) => &rest[start + 2 + end + 2..],
+            None => "",
+        };
+    }
+    out.push_str(rest);
+    out
+}
+
+fn items(css: &str) -> Result<Vec<Item<'_>>> {
+    let mut items = Vec::new();
+    let mut rest = css.trim_start();
+    while !rest.is_empty() {
+        match find_top_level(rest, &['{', ';']) {
+            Some(i) if rest[i..].starts_with(';') => {
+                items.push(Item::Statement(rest[..i].trim()));
+                rest = &rest[i + 1..];
+            }
+            Some(open) => {
+                let Some(close) = matching_brace(rest, open) else {
+                    bail!("unclosed `{{` after `{}`", rest[..open].trim());
+                };
+                items.push(Item::Block(rest[..open].trim(), &rest[open + 1..close]));
+                rest = &rest[close + 1..];
+            }
+            None => bail!("expected `{{` after `{}`", rest.trim()),
+        }
+        rest = rest.trim_start();
+    }
+    Ok(items)
+}
+
+// The first of `chars` outside of strings and parentheses.
+fn find_top_level(s: &str, chars: &[char]) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            (None, c) if depth == 0 && chars.contains(&c) => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn matching_brace(s: &str, open: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote = None;
+    for (i, c) in s[open..].char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '{') => depth += 1,
+            (None, '}') => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// Split at `separator` outside of parentheses, e.g. the layers of a `box-shadow`.
+fn split_top_level(s: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = s;
+    while let Some(i) = find_top_level(rest, &[separator]) {
+        parts.push(rest[..i].trim());
+        rest = &rest[i + separator.len_utf8()..];
+    }
+    parts.push(rest.trim());
+    parts.retain(|p| !p.is_empty());
+    parts
+}
+
+#[derive(Default)]
+struct Importer {
+    styles: StyleMap,
+    report: Vec<Unsupported>,
+}
+
+impl Importer {
+    fn unsupported(&mut self, selector: &str, declaration: &str, reason: impl Into<String>) {
+        self.report.push(Unsupported {
+            selector: selector.to_string(),
+            declaration: declaration.to_string(),
+            reason: reason.into(),
+        });
+    }
+
+    fn item(&mut self, query: Option<MediaQuery>, item: Item) {
+        let (prelude, body) = match item {
+            Item::Statement(statement) => {
+                return self.unsupported(statement, "", "unsupported at-rule");
+            }
+            Item::Block(prelude, body) => (prelude, body),
+        };
+
+        if let Some(condition) = prelude.strip_prefix("@media") {
+            if query.is_some() {
+                return self.unsupported(prelude, "", "nested `@media` blocks are not supported");
+            }
+            let query = match parse_media(condition) {
+                Ok(query) => query,
+                Err(e) => return self.unsupported(prelude, "", e),
+            };
+            match items(body) {
+                Ok(items) => items
+                    .into_iter()
+                    .for_each(|item| self.item(Some(query), item)),
+                Err(e) => self.unsupported(prelude, "", e.to_string()),
+            }
+            return;
+        }
+        if prelude.starts_with('@') {
+            return self.unsupported(prelude, "", "unsupported at-rule");
+        }
+
+        let mut targets = Vec::new();
+        for selector in prelude.split(',').map(str::trim) {
+            match parse_selector(selector) {
+                Ok(target) => targets.push(target),
+                Err(e) => self.unsupported(selector, "", e),
+            }
+        }
+        if targets.is_empty() {
+            return;
+        }
+
+        let mut rule = StyleRule::default();
+        for declaration in split_top_level(body, ';') {
+            let Some((property, value)) = declaration.split_once(':') else {
+                self.unsupported(prelude, declaration, "expected `property: value`");
+                continue;
+            };
+            let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+            let value = value.strip_suffix("!important").unwrap_or(&value).trim();
+            let properties = match css_declaration(&property.trim().to_lowercase(), value) {
+                Ok(properties) => properties,
+                Err(e) => {
+                    self.unsupported(prelude, declaration, e);
+                    continue;
+                }
+            };
+            // Of a shorthand such as `border: 1px dotted red`, keep what converts
+            // and report only the rest.
+            let shorthand = properties.len() > 1;
+            for (key, value) in properties {
+                match serde_json::from_value::<StyleRule>(json!({ &key: value })) {
+                    Ok(property) => rule.merge(&property),
+                    Err(e) if shorthand => {
+                        self.unsupported(prelude, declaration, format!("`{}`: {}", key, e))
+                    }
+                    Err(e) => self.unsupported(prelude, declaration, e.to_string()),
+                }
+            }
+        }
+
+        for (class, state) in targets {
+            let mut target = self.styles.entry(class).or_default();
+            if let Some(query) = query {
+                target = match target.media.iter().position(|(q, _)| *q == query) {
+                    Some(i) => &mut target.media[i].1,
+                    None => {
+                        target.media.push((query, StyleRule::default()));
+                        &mut target.media.last_mut().unwrap().1
+                    }
+                };
+            }
+            let target = match state {
+                Some("hover") => &mut **target.hover.get_or_insert_default(),
+                Some("active") => &mut **target.active.get_or_insert_default(),
+                Some("focus") => &mut **target.focus.get_or_insert_default(),
+                Some("disabled") => &mut **target.disabled.get_or_insert_default(),
+                _ => target,
+            };
+            target.merge(&rule);
+        }
+    }
+}
+
+// `.class` or `.class:hover`, as the class and the pseudo-state.
+fn parse_selector(selector: &str) -> Result<(String, Option<&'static str>), String> {
+    let unsupported = || {
+        format!(
+            "only `.class` selectors are supported, optionally with one of {}",
+            STATES
+                .iter()
+                .map(|s| format!("`:{}`", s))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    };
+    let name = selector.strip_prefix('.').ok_or_else(unsupported)?;
+    let (name, state) = match name.split_once(':') {
+        Some((name, state)) => {
+            let state = STATES
+                .iter()
+                .find(|s| **s == state)
+                .ok_or_else(unsupported)?;
+            (name, Some(*state))
+        }
+        None => (name, None),
+    };
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+    if name.is_empty() || !name.chars().all(is_name_char) {
+        return Err(unsupported());
+    }
+    Ok((name.to_string(), state))
+}
+
+fn parse_media(condition: &str) -> Result<MediaQuery, String> {
+    let condition = condition.trim();
+    let condition = ["screen and ", "all and "]
+        .iter()
+        .find_map(|media_type| condition.strip_prefix(media_type))
+        .unwrap_or(condition);
+    format!(
+        "@media{}",
+        condition.replace("prefers-color-scheme", "theme")
+    )
+    .parse()
+}
+
+// The `StyleRule` properties of a CSS declaration, to be checked by deserializing them.
+fn css_declaration(property: &str, value: &str) -> Result<Map<String, Value>, String> {
+    let one = |key: &str, value: Value| Ok(Map::from_iter([(key.to_string(), value)]));
+    let many = |properties: Vec<(&str, Value)>| {
+        Ok(properties
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect())
+    };
+    let words: Vec<&str> = split_top_level(value, ' ');
+
+    match property {
+        "margin" | "padding" | "inset" | "border-width" => {
+            let (shorthand, sides) = match property {
+                "margin" => (
+                    "margin",
+                    ["margin_top", "margin_right", "margin_bottom", "margin_left"],
+                ),
+                "padding" => (
+                    "padding",
+                    [
+                        "padding_top",
+                        "padding_right",
+                        "padding_bottom",
+                        "padding_left",
+                    ],
+                ),
+                "inset" => ("inset", ["top", "right", "bottom", "left"]),
+                _ => (
+                    "border_width",
+                    ["border_top", "border_right", "border_bottom", "border_left"],
+                ),
+            };
+            let kind = match property {
+                "border-width" => CssValue::Pixels,
+                _ => CssValue::Length,
+            };
+            expand_sides(shorthand, sides, &words, kind)
+        }
+        "border-radius" => expand_sides(
+            "rounded",
+            ["rounded_tl", "rounded_tr", "rounded_br", "rounded_bl"],
+            &words,
+            CssValue::Pixels,
+        ),
+        "gap" if words.len() == 2 => many(vec![
+            ("gap_y", json_value(CssValue::Length, words[0])),
+            ("gap_x", json_value(CssValue::Length, words[1])),
+        ]),
+        "line-height" if value.parse::<f64>().is_ok() => {
+            // A multiple of the font size; plain numbers are pixels in a styles file.
+            one(
+                "line_height",
+                json!(format!("{}%", value.parse::<f64>().unwrap() * 100.0)),
+            )
+        }
+        "border" | "border-top" | "border-right" | "border-bottom" | "border-left" => {
+            let width_key = match property {
+                "border" => "border_width",
+                _ => PROPERTIES
+                    .iter()
+                    .find(|(_, css, _)| css.strip_suffix("-width") == Some(property))
+                    .map(|(key, _, _)| *key)
+                    .unwrap_or_default(),
+            };
+            let mut properties = Vec::new();
+            for word in words {
+                if word == "none" {
+                    properties.push((width_key, json!(0)));
+                } else if word == "solid" || word == "dashed" || word == "dotted" {
+                    properties.push(("border_style", json!(word)));
+                } else if word.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+                    properties.push((width_key, json_value(CssValue::Pixels, word)));
+                } else {
+                    properties.push(("border_color", json_value(CssValue::Color, word)));
+                }
+            }
+            if property != "border" && properties.iter().any(|(k, _)| *k != width_key) {
+                return Err("only the width of a single side can be set".to_string());
+            }
+            many(properties)
+        }
+        "background" | "background-image" => {
+            if let Some((path, rest)) = value
+                .strip_prefix("url(")
+                .and_then(|url| url.split_once(')'))
+            {
+                // CSS draws an image at its own size unless the shorthand gives a size.
+                let fit = match rest.split_once('/').map(|(_, size)| size.trim()) {
+                    Some(size) if size.starts_with("cover") => "cover",
+                    Some(size) if size.starts_with("contain") => "contain",
+                    Some(size) if size.starts_with("100% 100%") => "fill",
+                    _ => "none",
+                };
+                let path = path.trim_matches(['"', '\'']);
+                return one("background", json!({ "image": path, "fit": fit }));
+            }
+            match value.strip_prefix("linear-gradient(") {
+                Some(gradient) => one(
+                    "background",
+                    parse_gradient(gradient.strip_suffix(')').unwrap_or(gradient))?,
+                ),
+                None if property == "background" => {
+                    one("bg_color", json_value(CssValue::Color, value))
+                }
+                None => Err("only linear gradients and images are supported".to_string()),
+            }
+        }
+        "flex" => {
+            let (grow, shrink, basis) = match words.as_slice() {
+                ["none"] => (json!(0), json!(0), json!("auto")),
+                ["auto"] => (json!(1), json!(1), json!("auto")),
+                [grow] if grow.parse::<f64>().is_ok() => {
+                    (json_value(CssValue::Number, grow), json!(1), json!("0%"))
+                }
+                [grow, shrink] if shrink.parse::<f64>().is_ok() => (
+                    json_value(CssValue::Number, grow),
+                    json_value(CssValue::Number, shrink),
+                    json!("0%"),
+                ),
+                [grow, basis] => (
+                    json_value(CssValue::Number, grow),
+                    json!(1),
+                    json_value(CssValue::Length, basis),
+                ),
+                [grow, shrink, basis] => (
+                    json_value(CssValue::Number, grow),
+                    json_value(CssValue::Number, shrink),
+                    json_value(CssValue::Length, basis),
+                ),
+                _ => return Err("expected `none`, `auto` or `grow [shrink] [basis]`".to_string()),
+            };
+            many(vec![
+                ("flex_grow", grow),
+                ("flex_shrink", shrink),
+                ("flex_basis", basis),
+            ])
+        }
+        "overflow" => match words.as_slice() {
+            [both] => many(vec![
+                ("overflow_x", json!(both)),
+                ("overflow_y", json!(both)),
+            ]),
+            [x, y] => many(vec![("overflow_x", json!(x)), ("overflow_y", json!(y))]),
+            _ => Err("expected one or two values".to_string()),
+        },
+        "font-style" => match value {
+            "italic" | "oblique" => one("italic", json!(true)),
+            "normal" => one("italic", json!(false)),
+            _ => Err("expected `italic` or `normal`".to_string()),
+        },
+        "text-decoration" | "text-decoration-line" => {
+            let mut properties = vec![("underline", json!(false)), ("line_through", json!(false))];
+            for word in words {
+                match word {
+                    "none" => {}
+                    "underline" => properties[0].1 = json!(true),
+                    "line-through" => properties[1].1 = json!(true),
+                    _ => return Err(format!("unsupported text decoration `{}`", word)),
+                }
+            }
+            many(properties)
+        }
+        "visibility" => match value {
+            "visible" => one("visible", json!(true)),
+            "hidden" | "collapse" => one("visible", json!(false)),
+            _ => Err("expected `visible` or `hidden`".to_string()),
+        },
+        "text-overflow" => match value {
+            "ellipsis" => one("text_ellipsis", json!(true)),
+            "clip" => one("text_ellipsis", json!(false)),
+            _ => Err("expected `ellipsis` or `clip`".to_string()),
+        },
+        "box-shadow" => match value {
+            "none" => one("shadow", json!("none")),
+            _ => {
+                let shadows = split_top_level(value, ',')
+                    .into_iter()
+                    .map(parse_shadow)
+                    .collect::<Result<Vec<_>, _>>()?;
+                one("shadow", Value::Array(shadows))
+            }
+        },
+        "grid-template-columns" | "grid-template-rows" => {
+            let key = match property {
+                "grid-template-columns" => "grid_cols",
+                _ => "grid_rows",
+            };
+            let count = match value.strip_prefix("repeat(") {
+                Some(repeat) => repeat.split(',').next().and_then(|n| n.trim().parse().ok()),
+                None if words.iter().all(|w| *w == "1fr") => Some(words.len()),
+                None => None,
+            };
+            match count {
+                Some(count) => one(key, json!(count)),
+                None => Err(
+                    "only equally sized tracks are supported, e.g. `repeat(3, 1fr)`".to_string(),
+                ),
+            }
+        }
+        "grid-column" | "grid-row" => {
+            let (span, start, end) = match property {
+                "grid-column" => ("col_span", "col_start", "col_end"),
+                _ => ("row_span", "row_start", "row_end"),
+            };
+            let lines: Vec<&str> = value.split('/').map(str::trim).collect();
+            match lines.as_slice() {
+                ["1", "-1"] => one(span, json!("full")),
+                [first, ..] if first.starts_with("span ") => one(
+                    span,
+                    json_value(CssValue::Number, first["span ".len()..].trim()),
+                ),
+                [first] => one(start, json_value(CssValue::Number, first)),
+                [first, last] => many(vec![
+                    (start, json_value(CssValue::Number, first)),
+                    (end, json_value(CssValue::Number, last)),
+                ]),
+                _ => Err("expected `start / end` or `span n`".to_string()),
+            }
+        }
+        "transition" => one("transition", parse_transition(value)?),
+        _ => match PROPERTIES.iter().find(|(_, css, _)| *css == property) {
+            Some((key, _, kind)) => one(key, json_value(*kind, value)),
+            None => Err("unsupported property".to_string()),
+        },
+    }
+}
+
+// A CSS value as a styles file value, to be checked when deserialized.
+fn json_value(kind: CssValue, value: &str) -> Value {
+    let number = |n: &str| n.parse::<f64>().ok().map(|n| json!(n));
+    match kind {
+        CssValue::Length => {
+            number(value.strip_suffix("px").unwrap_or(value)).unwrap_or_else(|| json!(value))
+        }
+        CssValue::Pixels => match value.strip_suffix("rem") {
+            // gpui's radius presets are in rems.
+            Some(rems) => radius_preset(rems).map_or(json!(value), |preset| json!(preset)),
+            None => {
+                number(value.strip_suffix("px").unwrap_or(value)).unwrap_or_else(|| json!(value))
+            }
+        },
+        CssValue::Number => match value.strip_suffix('%') {
+            Some(percent) => number(percent)
+                .map(|n| json!(n.as_f64().unwrap_or_default() / 100.0))
+                .unwrap_or_else(|| json!(value)),
+            None => number(value).unwrap_or_else(|| json!(value)),
+        },
+        CssValue::Color => match value
+            .strip_prefix("var(--theme-")
+            .and_then(|name| name.strip_suffix(')'))
+        {
+            Some(name) => json!(format!("theme.{}", name.replace('-', "_"))),
+            None => json!(value),
+        },
+        CssValue::Keyword => {
+            // The first of a `font-family` list.
+            let first = value.split(',').next().unwrap_or(value).trim();
+            json!(first.trim_matches(|c| c == '"' || c == '\''))
+        }
+    }
+}
+
+fn radius_preset(rems: &str) -> Option<&'static str> {
+    let rems = rems.parse::<f32>().ok()?;
+    ["xs", "sm", "md", "lg", "xl", "2xl", "3xl"]
+        .into_iter()
+        .find(|preset| {
+            let radius = serde_json::from_value::<StyleRadius>(json!(preset));
+            matches!(
+                radius.map(gpui::AbsoluteLength::from),
+                Ok(gpui::AbsoluteLength::Rems(r)) if r.0 == rems
+            )
+        })
+}
+
+// 1 to 4 values in CSS order: top, right, bottom, left.
+fn expand_sides(
+    shorthand: &str,
+    sides: [&str; 4],
+    words: &[&str],
+    kind: CssValue,
+) -> Result<Map<String, Value>, String> {
+    let [top, right, bottom, left] = match *words {
+        [all] => {
+            return Ok(Map::from_iter([(
+                shorthand.to_string(),
+                json_value(kind, all),
+            )]))
+        }
+        [y, x] => [y, x, y, x],
+        [top, x, bottom] => [top, x, bottom, x],
+        [top, right, bottom, left] => [top, right, bottom, left],
+        _ => return Err("expected 1 to 4 values".to_string()),
+    };
+    Ok(sides
+        .iter()
+        .zip([top, right, bottom, left])
+        .map(|(side, v)| (side.to_string(), json_value(kind, v)))
+        .collect())
+}
+
+fn parse_gradient(args: &str) -> Result<Value, String> {
+    let mut args = split_top_level(args, ',');
+    let angle = match args.first().copied() {
+        Some(first) if first.ends_with("deg") => {
+            args.remove(0);
+            first
+                .trim_end_matches("deg")
+                .parse::<f64>()
+                .map_err(|_| format!("invalid angle `{}`", first))?
+        }
+        Some(first) if first.starts_with("to ") => {
+            args.remove(0);
+            match first {
+                "to top" => 0.,
+                "to right" => 90.,
+                "to bottom" => 180.,
+                "to left" => 270.,
+                _ => return Err(format!("unsupported direction `{}`", first)),
+            }
+        }
+        _ => 180.,
+    };
+    if args.len() != 2 {
+        return Err("gpui gradients have exactly two color stops".to_string());
+    }
+
+    let stops: Vec<Value> = args
+        .iter()
+        .map(|stop| {
+            let words = split_top_level(stop, ' ');
+            match words.split_last() {
+                Some((position, color)) if position.ends_with('%') && !color.is_empty() => {
+                    let position = position.trim_end_matches('%').parse::<f64>().unwrap_or(0.);
+                    json!({
+                        "color": json_value(CssValue::Color, &color.join(" ")),
+                        "position": position / 100.0,
+                    })
+                }
+                _ => json_value(CssValue::Color, stop),
+            }
+        })
+        .collect();
+    Ok(json!({ "linear_gradient": { "angle": angle, "stops": stops } }))
+}
+
+fn parse_shadow(shadow: &str) -> Result<Value, String> {
+    let mut lengths = Vec::new();
+    let mut color = Vec::new();
+    for word in split_top_level(shadow, ' ') {
+        if word == "inset" {
+            return Err("inset shadows are not supported".to_string());
+        }
+        match word.strip_suffix("px").unwrap_or(word).parse::<f64>() {
+            Ok(n) if color.is_empty() => lengths.push(n),
+            _ => color.push(word),
+        }
+    }
+    if lengths.len() < 2 || lengths.len() > 4 {
+        return Err(format!("invalid shadow `{}`", shadow));
+    }
+    lengths.resize(4, 0.);
+
+    let mut value = json!({
+        "offset_x": lengths[0],
+        "offset_y": lengths[1],
+        "blur": lengths[2],
+        "spread": lengths[3],
+    });
+    if !color.is_empty() {
+        value["color"] = json_value(CssValue::Color, &color.join(" "));
+    }
+    Ok(value)
+}
+
+// gpui animates all transitioned properties together, so every entry must have
+// the same duration and easing.
+fn parse_transition(value: &str) -> Result<Value, String> {
+    let mut properties = Vec::new();
+    let mut timing: Option<(u64, &str)> = None;
+    for transition in split_top_level(value, ',') {
+        let mut property = "all";
+        let mut duration = None;
+        let mut easing = "ease-in-out";
+        for word in split_top_level(transition, ' ') {
+            if let Some(ms) = word.strip_suffix("ms").and_then(|n| n.parse::<f64>().ok()) {
+                duration = Some(ms as u64);
+            } else if let Some(s) = word.strip_suffix('s').and_then(|n| n.parse::<f64>().ok()) {
+                duration = Some((s * 1000.0) as u64);
+            } else if ["linear", "ease-in", "ease-out", "ease-in-out"].contains(&word) {
+                easing = word;
+            } else if word == "ease" {
+                easing = "ease-in-out";
+            } else {
+                property = word;
+            }
+        }
+        let duration = duration.ok_or_else(|| format!("missing duration in `{}`", transition))?;
+        if timing.is_some_and(|t| t != (duration, easing)) {
+            return Err("all transitions must have the same duration and easing".to_string());
+        }
+        timing = Some((duration, easing));
+
+        let name = TRANSITION_PROPERTIES
+            .iter()
+            .find(|(_, css)| *css == property)
+            .map(|(name, _)| *name)
+            .ok_or_else(|| format!("`{}` can't be transitioned", property))?;
+        properties.push(name);
+    }
+    let (duration, easing) = timing.ok_or("empty transition")?;
+    if properties.contains(&"all") {
+        properties.clear();
+    }
+    Ok(json!({ "duration_ms": duration, "easing": easing, "properties": properties }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{css_to_style_map, style_map_to_css, style_sheet_to_css};
+    use crate::my_style_data::{
+        parse_style_map, parse_style_sheet, MediaQuery, StyleColor, StyleLength,
+    };
+    use gpui::rgb;
+
+    #[test]
+    fn test_css_round_trip() {
+        let styles = parse_style_map(
+            r##"{
+                "card": {
+                    "display": "flex", "padding": 8, "width": "50%", "bg_color": "#ff0000",
+                    "min_width": "1/4", "max_width": "full", "border_color": "red / 0.5",
+                    "text_color": "theme.muted_foreground", "rounded": "lg", "italic": true,
+                    "shadow": [{ "offset_y": 2, "blur": 4, "color": "#00000080" }],
+                    "transition": { "duration_ms": 150, "properties": ["bg_color"] },
+                    "hover": { "opacity": 0.5 },
+                    "breakpoints": { "md": { "flex_direction": "row" } }
+                },
+                "hero": { "background": { "image": "images/hero.png", "fit": "contain" } }
+            }"##,
+        )
+        .unwrap();
+
+        let (css, report) = style_map_to_css(&styles);
+        assert!(report.is_empty(), "{:?}", report);
+        assert!(css.contains("  display: flex;\n"));
+        assert!(css.contains("  min-width: 25%;\n"));
+        assert!(css.contains("  max-width: 100%;\n"));
+        assert!(css.contains("  border-color: #ff000080;\n"));
+        assert!(css.contains("  color: var(--theme-muted-foreground);\n"));
+        assert!(css.contains("  border-radius: 0.5rem;\n"));
+        assert!(css.contains(".card:hover {\n  opacity: 0.5;\n}"));
+        assert!(css.contains("@media (min-width: 768px) {\n  .card {\n    flex-direction: row;\n"));
+        assert!(
+            css.contains("  background: url(\"images/hero.png\") center / contain no-repeat;\n")
+        );
+
+        let (imported, report) = css_to_style_map(&css).unwrap();
+        assert!(report.is_empty(), "{:?}", report);
+        let card = &imported["card"];
+        assert_eq!(card.padding, Some(StyleLength::Px(8.0)));
+        assert_eq!(card.text_color, styles["card"].text_color);
+        assert_eq!(card.shadow, styles["card"].shadow);
+        assert_eq!(card.transition, styles["card"].transition);
+        assert_eq!(card.hover, styles["card"].hover);
+        assert_eq!(
+            card.media[0].0,
+            "@media(min_width: 768)".parse::<MediaQuery>().unwrap()
+        );
+        assert_eq!(imported["hero"].background, styles["hero"].background);
+    }
+
+    #[test]
+    fn test_css_export_selectors() {
+        let (styles, selectors) = parse_style_sheet(
+            r#"{
+                "card": { "opacity": 0.5 },
+                "selectors": {
+                    ".sidebar label": { "opacity": 0.5 },
+                    "button#ok.primary": { "hover": { "opacity": 1 } }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let (css, report) = style_sheet_to_css(&styles, &selectors);
+        assert!(report.is_empty(), "{:?}", report);
+        assert_eq!(
+            css,
+            ".card {\n  opacity: 0.5;\n}\n\n.sidebar label {\n  opacity: 0.5;\n}\n\nbutton#ok.primary:hover {\n  opacity: 1;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_css_import_report() {
+        let (styles, report) = css_to_style_map(
+            "/* from the prototype */
+            @import url(base.css);
+            .a, .b:hover { margin: 1px 2px; color: tomato; transform: rotate(4deg); display: inline }
+            div > .c { width: 1px }
+            .d { border: 2px dotted red }
+            @media (prefers-color-scheme: dark) { .a { background: #000 } }",
+        )
+        .unwrap();
+
+        assert_eq!(styles["a"].margin_right, Some(StyleLength::Px(2.0)));
+        assert_eq!(
+            styles["b"].hover.as_ref().unwrap().text_color,
+            Some(StyleColor::Rgba(rgb(0xff6347)))
+        );
+        assert_eq!(
+            styles["a"].media[0].1.bg_color,
+            Some(StyleColor::Rgba(rgb(0x000000)))
+        );
+        assert!(!styles.contains_key("c"));
+        // Only the border style of the shorthand is dropped.
+        assert!(styles["d"].border_width.is_some());
+        assert_eq!(
+            styles["d"].border_color,
+            Some(StyleColor::Rgba(rgb(0xff0000)))
+        );
+
+        let unsupported: Vec<String> = report.iter().map(|u| u.declaration.clone()).collect();
+        assert_eq!(
+            unsupported,
+            [
+                "",
+                "transform: rotate(4deg)",
+                "display: inline",
+                "",
+                "border: 2px dotted red"
+            ]
+        );
+        assert_eq!(report[3].selector, "div > .c");
+        assert!(report[4]
+            .reason
+            .starts_with("`border_style`: unknown variant `dotted`"));
+
+        assert!(css_to_style_map(".a { width: 1px").is_err());
+    }
+}
@@ -0,0 +1,307 @@
+use anyhow::{Context as _, Result};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::Button,
+    input::{self, InputEvent, InputState},
+    label::Label,
+    ActiveTheme,
+};
+use std::path::Path;
+
+use crate::my_style_data::{parse_styles, SetMyStyleData};
+use crate::pjson_edit;
+
+// A panel listing the classes of the styles of `T`, to tune their properties
+// in the running app. A value is applied and written back to the styles file
+// when Enter is pressed in its input; an empty value removes the property.
+// Values are shown as written in the styles file, `$var` references included.
+// Properties inherited through `extends` show their resolved value, and are
+// only written to the class once they are changed.
+//
+// Render it as a child of the view, and `toggle` it from a button or shortcut:
+// `let host = cx.weak_entity(); cx.new(|_| StyleEditor::new(host))`.
+pub struct StyleEditor<T> {
+    host: WeakEntity<T>,
+    open: bool,
+    class: Option<String>,
+    // The properties of `class` with an input for each value.
+    fields: Vec<Field>,
+    // `property: value` to add to `class`.
+    new_field: Option<Entity<InputState>>,
+    error: Option<String>,
+    _subscriptions: Vec<Subscription>,
+}
+
+struct Field {
+    property: String,
+    input: Entity<InputState>,
+    // The text shown or last written, so that an unchanged value is never
+    // written back.
+    original: String,
+}
+
+impl<T: SetMyStyleData + 'static> StyleEditor<T> {
+    pub fn new(host: WeakEntity<T>) -> Self {
+        Self {
+            host,
+            open: false,
+            class: None,
+            fields: Vec::new(),
+            new_field: None,
+            error: None,
+            _subscriptions: Vec::new(),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn toggle(&mut self, cx: &mut Context<Self>) {
+        self.open = !self.open;
+        cx.notify();
+    }
+
+    fn select(&mut self, class: String, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(host) = self.host.upgrade() else {
+            return;
+        };
+        let style_data = host.read(cx).get_style_data();
+        let mut properties = style_data
+            .source()
+            .and_then(|path| source_properties(path, &class))
+            .unwrap_or_default();
+        if let Some(serde_json::Value::Object(resolved)) =
+            style_data.style_map.get(&class).map(|rule| rule.to_json())
+        {
+            for (property, value) in resolved {
+                properties.entry(property).or_insert(value);
+            }
+        }
+
+        self.fields.clear();
+        self._subscriptions.clear();
+        for (property, value) in properties {
+            let text = value_text(value);
+            let input = cx.new(|cx| InputState::new(window, cx).default_value(text.clone()));
+            let name = property.clone();
+            self._subscriptions.push(cx.subscribe_in(
+                &input,
+                window,
+                move |this, input, event: &InputEvent, _, cx| {
+                    if let InputEvent::PressEnter { .. } = event {
+                        let text = input.read(cx).value().to_string();
+                        this.edit(&name, &text, cx);
+                    }
+                },
+            ));
+            self.fields.push(Field {
+                property,
+                input,
+                original: text,
+            });
+        }
+
+        let new_field = cx.new(|cx| InputState::new(window, cx));
+        self._subscriptions.push(cx.subscribe_in(
+            &new_field,
+            window,
+            |this, input, event: &InputEvent, window, cx| {
+                if let InputEvent::PressEnter { .. } = event {
+                    let text = input.read(cx).value().to_string();
+                    match text.split_once(':') {
+                        Some((property, value)) => {
+                            this.edit(property.trim(), value, cx);
+                            if this.error.is_none() {
+                                // Show the new property with the others.
+                                if let Some(class) = this.class.clone() {
+                                    this.select(class, window, cx);
+                                }
+                            }
+                        }
+                        None => {
+                            this.error = Some("Expected `property: value`".to_string());
+                            cx.notify();
+                        }
+                    }
+                }
+            },
+        ));
+        self.new_field = Some(new_field);
+
+        self.class = Some(class);
+        self.error = None;
+        cx.notify();
+    }
+
+    fn edit(&mut self, property: &str, text: &str, cx: &mut Context<Self>) {
+        let Some(class) = self.class.clone() else {
+            return;
+        };
+        let field = self.fields.iter().position(|f| f.property == property);
+        if field.is_some_and(|i| self.fields[i].original == text.trim()) {
+            return;
+        }
+        self.error = self
+            .write(&class, property, text, cx)
+            .err()
+            .map(|e| format!("{:#}", e));
+        if let Some(i) = field.filter(|_| self.error.is_none()) {
+            self.fields[i].original = text.trim().to_string();
+        }
+        cx.notify();
+    }
+
+    fn write(&mut self, class: &str, property: &str, text: &str, cx: &mut App) -> Result<()> {
+        let host = self.host.upgrade().context("The styled view is gone")?;
+        let path = host
+            .read(cx)
+            .get_style_data()
+            .source()
+            .context("The styles were not loaded from a file")?
+            .to_path_buf();
+
+        let source = std::fs::read_to_string(&path)?;
+        let edited = if text.trim().is_empty() {
+            pjson_edit::remove_value(&source, &[class, property])?
+        } else {
+            // `12`, `true` or `{ ... }` as json, anything else as a string.
+            let value = serde_json::from_str(text.trim())
+                .unwrap_or_else(|_| serde_json::Value::String(text.trim().to_string()));
+            pjson_edit::set_value(&source, &[class, property], &value)?
+        };
+
+        // Parse the whole file first, so that an invalid value never gets written.
+        let style_data = parse_styles(edited.as_bytes())?.with_source(path.clone());
+        std::fs::write(&path, edited)?;
+
+        // Apply right away instead of waiting for the file watcher.
+        host.update(cx, |this, cx| {
+            style_data.keep_state_of(this.get_style_data());
+            style_data.sync_theme(cx);
+            this.set_style_data(style_data);
+            cx.notify();
+        });
+        Ok(())
+    }
+}
+
+impl<T: SetMyStyleData + 'static> Render for StyleEditor<T> {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(host) = self.host.upgrade().filter(|_| self.open) else {
+            return div().into_any_element();
+        };
+        let mut classes: Vec<String> = host
+            .read(cx)
+            .get_style_data()
+            .style_map
+            .keys()
+            .cloned()
+            .collect();
+        classes.sort();
+
+        let theme = cx.theme();
+        let class_list = div()
+            .flex()
+            .flex_wrap()
+            .gap_1()
+            .children(classes.into_iter().map(|class| {
+                let selected = self.class.as_ref() == Some(&class);
+                div()
+                    .id(SharedString::from(format!("style_editor_class_{}", class)))
+                    .px_1()
+                    .rounded_sm()
+                    .cursor_pointer()
+                    .when(selected, |d| d.bg(theme.accent))
+                    .hover(|d| d.bg(theme.muted))
+                    .child(class.clone())
+                    .on_click(cx.listener(move |this, _, window, cx| {
+                        this.select(class.clone(), window, cx)
+                    }))
+            }));
+
+        let fields = div()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .children(self.fields.iter().map(|field| {
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(Label::new(field.property.clone()).w(px(120.)))
+                    .child(input::Input::new(&field.input).flex_1())
+            }))
+            .children(self.new_field.as_ref().map(|input| {
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(Label::new("add").w(px(120.)))
+                    .child(input::Input::new(input).flex_1())
+            }));
+
+        div()
+            .id("style_editor")
+            .absolute()
+            .top_0()
+            .right_0()
+            .h_full()
+            .w(px(380.))
+            .flex()
+            .flex_col()
+            .gap_2()
+            .p_2()
+            .overflow_y_scroll()
+            .bg(theme.background)
+            .text_color(theme.foreground)
+            .border_l_1()
+            .border_color(theme.border)
+            .shadow_lg()
+            .child(
+                div()
+                    .flex()
+                    .justify_between()
+                    .items_center()
+                    .child(Label::new("Styles"))
+                    .child(
+                        Button::new("style_editor_close")
+                            .label("×")
+                            .on_click(cx.listener(|this, _, _, cx| this.toggle(cx))),
+                    ),
+            )
+            .child(class_list)
+            .children(self.class.clone().map(Label::new))
+            .child(fields)
+            .children(
+                self.error
+                    .clone()
+                    .map(|error| Label::new(error).text_color(theme.danger)),
+            )
+            .into_any_element()
+    }
+}
+
+// The properties of `class` as written in the styles file at `path`.
+fn source_properties(
+    path: &Path,
+    class: &str,
+) -> Option<serde_json::Map<String, serde_json::Value>> {
+    let source = std::fs::read(path).ok()?;
+    let json = pjson::PJsonReader::from_pjson(&source);
+    match serde_json::from_slice::<serde_json::Value>(&json)
+        .ok()?
+        .get_mut(class)?
+        .take()
+    {
+        serde_json::Value::Object(properties) => Some(properties),
+        _ => None,
+    }
+}
+
+fn value_text(value: serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,476 @@
+use serde_json::{json, Map, Value};
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::my_style_data::{parse_style_sheet, Breakpoint, StyleLength, StyleRule};
+use crate::pjson_edit::duplicate_keys;
+use crate::style_schema::serde_names;
+use crate::style_selector::Selector;
+use crate::style_utility::parse_utility_class;
+
+// Checks of a styles file against the layout files using it, see the
+// `style_lint` binary. `StylableElement::class` skips unknown classes silently,
+// so a typo in a layout only shows as a missing style; these checks catch it
+// before it is committed.
+//
+// Classes used only from Rust code look unused here.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintKind {
+    // A class in a layout (or an `extends`) that is neither defined nor a utility class.
+    UndefinedClass,
+    // A class defined in the styles file but not used by any layout.
+    UnusedClass,
+    // A class or property given twice; only the last one takes effect.
+    DuplicateKey,
+    // Properties of one rule that override each other.
+    ConflictingProperties,
+    // An unknown property, an invalid keyword or value, or a file that does not parse.
+    InvalidValue,
+}
+
+impl fmt::Display for LintKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LintKind::UndefinedClass => "undefined class",
+            LintKind::UnusedClass => "unused class",
+            LintKind::DuplicateKey => "duplicate key",
+            LintKind::ConflictingProperties => "conflicting properties",
+            LintKind::InvalidValue => "invalid value",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintIssue {
+    pub kind: LintKind,
+    // The file, and where in it: `styles.pjson: btn1.hover.bg_color`,
+    // `layout.pjson: div.div1 > label.label`.
+    pub location: String,
+    pub message: String,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.location, self.kind, self.message)
+    }
+}
+
+// Lint a styles file and the layouts using it, each given as
+// `(file name, pjson content)`.
+pub fn lint(styles: (&str, &str), layouts: &[(&str, &str)]) -> Vec<LintIssue> {
+    let mut linter = Linter::default();
+
+    let (file, source) = styles;
+    let (defined, mut used) = match linter.read(file, source) {
+        Some(root) => linter.lint_styles(file, &root),
+        None => Default::default(),
+    };
+
+    for (file, source) in layouts {
+        if let Some(root) = linter.read(file, source) {
+            linter.lint_element(file, &root, "", &defined, &mut used);
+        }
+    }
+
+    for class in defined.difference(&used) {
+        linter.issue(
+            LintKind::UnusedClass,
+            file,
+            class,
+            format!("`{}` is not used by any layout", class),
+        );
+    }
+
+    linter.issues
+}
+
+#[derive(Default)]
+struct Linter {
+    issues: Vec<LintIssue>,
+    vars: Map<String, Value>,
+}
+
+impl Linter {
+    fn issue(&mut self, kind: LintKind, file: &str, path: &str, message: String) {
+        let location = if path.is_empty() {
+            file.to_string()
+        } else {
+            format!("{}: {}", file, path)
+        };
+        self.issues.push(LintIssue {
+            kind,
+            location,
+            message,
+        });
+    }
+
+    fn errors(&self) -> usize {
+        let is_error = |issue: &&LintIssue| {
+            matches!(
+                issue.kind,
+                LintKind::UndefinedClass | LintKind::InvalidValue
+            )
+        };
+        self.issues.iter().filter(is_error).count()
+    }
+
+    // Decode a pjson file, reporting keys given twice.
+    fn read(&mut self, file: &str, source: &str) -> Option<Value> {
+        let json = pjson::PJsonReader::from_pjson(source.as_bytes());
+        let json = String::from_utf8_lossy(&json);
+        let root: Value = match serde_json::from_str(&json) {
+            Ok(root @ Value::Object(_)) => root,
+            Ok(_) => {
+                self.issue(
+                    LintKind::InvalidValue,
+                    file,
+                    "",
+                    "expected an object".into(),
+                );
+                return None;
+            }
+            Err(e) => {
+                self.issue(LintKind::InvalidValue, file, "", e.to_string());
+                return None;
+            }
+        };
+
+        match duplicate_keys(source) {
+            Ok(duplicates) => {
+                for path in duplicates {
+                    let key = path.last().cloned().unwrap_or_default();
+                    let message = format!("`{}` is given more than once", key);
+                    self.issue(LintKind::DuplicateKey, file, &path.join("."), message);
+                }
+            }
+            Err(e) => self.issue(LintKind::InvalidValue, file, "", format!("{:#}", e)),
+        }
+
+        Some(root)
+    }
+
+    // Check every rule of a styles file. Returns the defined classes and the
+    // classes used by `extends` and `selectors`.
+    fn lint_styles(&mut self, file: &str, root: &Value) -> (BTreeSet<String>, BTreeSet<String>) {
+        let Value::Object(root) = root else {
+            return Default::default();
+        };
+        if let Some(Value::Object(vars)) = root.get("vars") {
+            self.vars = vars.clone();
+        }
+
+        let mut rules = Vec::new();
+        let mut defined = BTreeSet::new();
+        let mut used = BTreeSet::new();
+        for (key, value) in root {
+            match (key.as_str(), value) {
+                ("vars", _) => {}
+                ("selectors", Value::Object(block)) => {
+                    for (selector, rule) in block {
+                        // An invalid selector is reported by `parse_style_sheet` below.
+                        if let Ok(selector) = selector.parse::<Selector>() {
+                            used.extend(selector.classes().map(str::to_string));
+                        }
+                        rules.push((format!("selectors.{}", selector), rule));
+                    }
+                }
+                ("themes", Value::Object(themes)) => {
+                    for (theme, block) in themes {
+                        let Value::Object(block) = block else {
+                            continue;
+                        };
+                        for (class, rule) in block {
+                            defined.insert(class.clone());
+                            rules.push((format!("themes.{}.{}", theme, class), rule));
+                        }
+                    }
+                }
+                (key, Value::Object(block)) if key.starts_with("@media") => {
+                    for (class, rule) in block {
+                        defined.insert(class.clone());
+                        rules.push((format!("{}.{}", key, class), rule));
+                    }
+                }
+                (class, rule) => {
+                    defined.insert(class.to_string());
+                    rules.push((class.to_string(), rule));
+                }
+            }
+        }
+
+        let errors = self.errors();
+        for (path, rule) in rules {
+            for class in extends(rule) {
+                if !defined.contains(class) {
+                    let message = format!("extends undefined class `{}`", class);
+                    self.issue(LintKind::UndefinedClass, file, &path, message);
+                }
+                used.insert(class.to_string());
+            }
+            self.lint_rule(file, &path, rule);
+        }
+
+        // Whatever the checks above do not cover, e.g. invalid `@media`
+        // conditions, selectors or `extends` cycles. Only reported if nothing
+        // else was, as it would just repeat the first problem found.
+        if self.errors() > errors {
+            return (defined, used);
+        }
+        if let Err(e) = parse_style_sheet(&Value::Object(root.clone()).to_string()) {
+            self.issue(LintKind::InvalidValue, file, "", format!("{:#}", e));
+        }
+
+        (defined, used)
+    }
+
+    // Check the properties of a rule one by one, so that every invalid one is
+    // reported rather than only the first.
+    fn lint_rule(&mut self, file: &str, path: &str, rule: &Value) {
+        let Value::Object(rule) = rule else {
+            self.issue(
+                LintKind::InvalidValue,
+                file,
+                path,
+                "expected an object".into(),
+            );
+            return;
+        };
+
+        for (key, value) in rule {
+            let property_path = format!("{}.{}", path, key);
+            match key.as_str() {
+                "extends" => {}
+                "hover" | "active" | "focus" | "disabled" => {
+                    self.lint_rule(file, &property_path, value)
+                }
+                "breakpoints" => {
+                    let Value::Object(breakpoints) = value else {
+                        let message = "expected an object of breakpoints".into();
+                        self.issue(LintKind::InvalidValue, file, &property_path, message);
+                        continue;
+                    };
+                    for (breakpoint, rule) in breakpoints {
+                        let path = format!("{}.{}", property_path, breakpoint);
+                        if !serde_names::<Breakpoint>().contains(&breakpoint.as_str()) {
+                            let message = format!(
+                                "unknown breakpoint `{}`, expected one of {}",
+                                breakpoint,
+                                serde_names::<Breakpoint>().join(", ")
+                            );
+                            self.issue(LintKind::InvalidValue, file, &path, message);
+                        }
+                        self.lint_rule(file, &path, rule);
+                    }
+                }
+                key if !serde_names::<StyleRule>().contains(&key) => {
+                    let message = format!("unknown property `{}`", key);
+                    self.issue(LintKind::InvalidValue, file, &property_path, message);
+                }
+                key => {
+                    let Some(value) = self.resolve_var(file, &property_path, value) else {
+                        continue;
+                    };
+                    if let Err(e) = serde_json::from_value::<StyleRule>(json!({ key: value })) {
+                        self.issue(LintKind::InvalidValue, file, &property_path, e.to_string());
+                    }
+                }
+            }
+        }
+
+        self.lint_conflicts(file, path, rule);
+    }
+
+    fn lint_conflicts(&mut self, file: &str, path: &str, rule: &Map<String, Value>) {
+        let mut conflict = |message: String| {
+            self.issue(LintKind::ConflictingProperties, file, path, message);
+        };
+
+        if rule.contains_key("bg_color") && rule.contains_key("background") {
+            conflict("`background` replaces `bg_color`".into());
+        }
+        if rule.get("size_full") == Some(&Value::Bool(true)) {
+            for key in ["width", "height"] {
+                if rule.contains_key(key) {
+                    conflict(format!("`{}` overrides `size_full`", key));
+                }
+            }
+        }
+
+        let length = |key: &str| {
+            let value = rule.get(key)?;
+            let value = match value.as_str().and_then(|s| s.strip_prefix('$')) {
+                Some(name) => self.vars.get(name)?,
+                None => value,
+            };
+            serde_json::from_value::<StyleLength>(value.clone()).ok()
+        };
+        let mut larger = Vec::new();
+        for (min, max) in [("min_width", "max_width"), ("min_height", "max_height")] {
+            let is_larger = match (length(min), length(max)) {
+                (Some(StyleLength::Px(a)), Some(StyleLength::Px(b)))
+                | (Some(StyleLength::Rems(a)), Some(StyleLength::Rems(b))) => a > b,
+                (Some(a), Some(b)) => match (a.relative(), b.relative()) {
+                    (Some(a), Some(b)) => a > b,
+                    _ => false,
+                },
+                _ => false,
+            };
+            if is_larger {
+                larger.push(format!("`{}` is larger than `{}`", min, max));
+            }
+        }
+        for message in larger {
+            self.issue(LintKind::ConflictingProperties, file, path, message);
+        }
+    }
+
+    // The value of a property, with a `"$name"` variable replaced.
+    fn resolve_var(&mut self, file: &str, path: &str, value: &Value) -> Option<Value> {
+        let Some(name) = value.as_str().and_then(|s| s.strip_prefix('$')) else {
+            return Some(value.clone());
+        };
+        let mut name = name;
+        let mut seen = vec![];
+        loop {
+            let value = self.vars.get(name);
+            match value
+                .and_then(|v| v.as_str())
+                .and_then(|s| s.strip_prefix('$'))
+            {
+                _ if seen.contains(&name) => {
+                    let message = format!("variable `{}` refers to itself", name);
+                    self.issue(LintKind::InvalidValue, file, path, message);
+                    return None;
+                }
+                Some(next) => {
+                    seen.push(name);
+                    name = next;
+                }
+                None => match value {
+                    Some(value) => return Some(value.clone()),
+                    None => {
+                        let message = format!("undefined variable `${}`", name);
+                        self.issue(LintKind::InvalidValue, file, path, message);
+                        return None;
+                    }
+                },
+            }
+        }
+    }
+
+    // Check the classes and inline style of a layout element and its children.
+    fn lint_element(
+        &mut self,
+        file: &str,
+        element: &Value,
+        parent: &str,
+        defined: &BTreeSet<String>,
+        used: &mut BTreeSet<String>,
+    ) {
+        let Value::Object(element) = element else {
+            return;
+        };
+
+        let class = element.get("class").and_then(|v| v.as_str()).unwrap_or("");
+        let mut path = element
+            .get("type")
+            .and_then(|v| v.as_str())
+            .unwrap_or("?")
+            .to_string();
+        if let Some(id) = element.get("id").and_then(|v| v.as_str()) {
+            path = format!("{}#{}", path, id);
+        }
+        for token in class.split_whitespace() {
+            path = format!("{}.{}", path, token);
+        }
+        if !parent.is_empty() {
+            path = format!("{} > {}", parent, path);
+        }
+
+        for token in class.split_whitespace() {
+            used.insert(token.to_string());
+            if defined.contains(token) {
+                continue;
+            }
+            match parse_utility_class(token) {
+                Some(Ok(_)) => {}
+                Some(Err(e)) => self.issue(LintKind::InvalidValue, file, &path, e),
+                None => {
+                    let message = format!("class `{}` is not defined", token);
+                    self.issue(LintKind::UndefinedClass, file, &path, message);
+                }
+            }
+        }
+
+        if let Some(style) = element.get("style") {
+            self.lint_rule(file, &format!("{} style", path), style);
+        }
+
+        if let Some(Value::Array(children)) = element.get("children") {
+            for child in children {
+                self.lint_element(file, child, &path, defined, used);
+            }
+        }
+    }
+}
+
+// The classes a rule extends, as a string or a list.
+fn extends(rule: &Value) -> Vec<&str> {
+    match rule.get("extends") {
+        Some(Value::String(class)) => vec![class.as_str()],
+        Some(Value::Array(classes)) => classes.iter().filter_map(|v| v.as_str()).collect(),
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lint, LintKind};
+
+    #[test]
+    fn test_lint() {
+        let styles = r##"{
+            vars: { accent: "#3366ff" },
+            base: { padding: 4 },
+            btn: { extends: "base", bg_color: "$accent", background: "#ffffff", width: 10, width: 12 },
+            card: { text_align: "centre", size_full: true, height: 20, min_width: 30, max_width: 20 },
+            unused: { extends: "missing", colour: "#000000" },
+            sidebar: { padding: 2 },
+            selectors: { ".sidebar label": { opacity: 0.5 } },
+        }"##;
+        let layout = r#"{
+            type: "div", class: "card",
+            children: [
+                { type: "button", id: "ok", class: "btn p-4 w-[abc]" },
+                { type: "label", class: "lable", style: { bg_color: "$nope" } },
+            ],
+        }"#;
+
+        let issues: Vec<(LintKind, String)> =
+            lint(("styles.pjson", styles), &[("layout.pjson", layout)])
+                .into_iter()
+                .map(|issue| (issue.kind, issue.to_string()))
+                .collect();
+        let expected = [
+            (LintKind::DuplicateKey, "styles.pjson: btn.width: duplicate key: `width` is given more than once"),
+            (LintKind::ConflictingProperties, "styles.pjson: btn: conflicting properties: `background` replaces `bg_color`"),
+            (LintKind::InvalidValue, "styles.pjson: card.text_align: invalid value: unknown variant `centre`, expected one of `left`, `center`, `right`"),
+            (LintKind::ConflictingProperties, "styles.pjson: card: conflicting properties: `height` overrides `size_full`"),
+            (LintKind::ConflictingProperties, "styles.pjson: card: conflicting properties: `min_width` is larger than `max_width`"),
+            (LintKind::UndefinedClass, "styles.pjson: unused: undefined class: extends undefined class `missing`"),
+            (LintKind::InvalidValue, "styles.pjson: unused.colour: invalid value: unknown property `colour`"),
+            (LintKind::InvalidValue, "layout.pjson: div.card > button#ok.btn.p-4.w-[abc]: invalid value: invalid utility class `w-[abc]`: invalid length `abc`, expected a number, \"12px\", \"1.5rem\", \"50%\", \"1/3\", \"full\" or \"auto\""),
+            (LintKind::UndefinedClass, "layout.pjson: div.card > label.lable: undefined class: class `lable` is not defined"),
+            (LintKind::InvalidValue, "layout.pjson: div.card > label.lable style.bg_color: invalid value: undefined variable `$nope`"),
+            (LintKind::UnusedClass, "styles.pjson: unused: unused class: `unused` is not used by any layout"),
+        ];
+        assert_eq!(
+            issues,
+            expected
+                .iter()
+                .map(|(kind, message)| (*kind, message.to_string()))
+                .collect::<Vec<_>>()
+        );
+    }
+}
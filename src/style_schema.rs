@@ -0,0 +1,494 @@
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde_json::{json, Map, Value};
+
+use crate::my_layout_data::{AttributeValue, LayoutElement, LAYOUT_ELEMENTS};
+use crate::my_style_data::{
+    Breakpoint, RadiusPreset, ShadowPreset, StyleAlignItems, StyleBorderStyle, StyleBoxShadow,
+    StyleCursor, StyleDisplay, StyleFlexDirection, StyleJustifyContent, StyleObjectFit,
+    StyleOverflow, StylePosition, StyleRule, StyleTextAlign, StyleTheme, StyleWhitespace,
+    THEME_COLOR_NAMES,
+};
+use crate::style_transition::{StyleEasing, StyleTransition, TransitionProperty};
+
+// JSON Schemas of styles and layout files, so that editors can autocomplete and
+// validate them. The generated schemas are shipped in `schemas/`; after changing
+// `StyleRule` or the layout elements, regenerate them with
+// `cargo run --bin style_schema`.
+
+pub const STYLES_SCHEMA: &str = include_str!("../schemas/styles.schema.json");
+pub const LAYOUT_SCHEMA: &str = include_str!("../schemas/layout.schema.json");
+
+const SCHEMA_VERSION: &str = "https://json-schema.org/draft/2020-12/schema";
+
+pub fn styles_schema() -> Value {
+    let classes = json!({ "type": "object", "additionalProperties": reference("rule") });
+    let mut media = classes.clone();
+    media["description"] = json!(
+        "Classes applied when the condition matches, e.g. `@media(max_width: 600) and (theme: dark)`."
+    );
+    let themes: Map<String, Value> = serde_names::<StyleTheme>()
+        .iter()
+        .map(|theme| (theme.to_string(), classes.clone()))
+        .collect();
+
+    json!({
+        "$schema": SCHEMA_VERSION,
+        "title": "Styles file",
+        "description": "Classes by name, plus the `vars`, `themes`, `selectors` and `@media(...)` blocks.",
+        "type": "object",
+        "properties": {
+            "vars": {
+                "description": "Variables, used as `\"$name\"` in place of any property value.",
+                "type": "object",
+            },
+            "themes": {
+                "description": "Overlays of classes for the light or dark theme.",
+                "type": "object",
+                "properties": themes,
+                "additionalProperties": false,
+            },
+            "selectors": {
+                "description": "Rules for the elements of layout files, keyed by a selector such as `button`, `#id` or `.sidebar label`.",
+                "type": "object",
+                "additionalProperties": reference("rule"),
+            },
+        },
+        "patternProperties": { "^@media": media },
+        "additionalProperties": reference("rule"),
+        "$defs": style_definitions(),
+    })
+}
+
+pub fn layout_schema() -> Value {
+    let element = |element: &LayoutElement| {
+        let mut properties = Map::new();
+        properties.insert("type".to_string(), json!({ "enum": element.types }));
+        let mut required = vec!["type"];
+        for attribute in element.attributes {
+            let mut schema = match attribute.value {
+                AttributeValue::String => json!({ "type": "string" }),
+                AttributeValue::Bool => json!({ "type": "boolean" }),
+                AttributeValue::Style => reference("rule"),
+                AttributeValue::Children => json!({
+                    "type": "array",
+                    "items": { "anyOf": [reference("element"), { "type": "string" }] },
+                }),
+            };
+            if !attribute.description.is_empty() {
+                schema["description"] = json!(attribute.description);
+            }
+            properties.insert(attribute.name.to_string(), schema);
+            if attribute.required {
+                required.push(attribute.name);
+            }
+        }
+        json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false,
+        })
+    };
+
+    let elements: Vec<Value> = LAYOUT_ELEMENTS.iter().map(element).collect();
+    let mut definitions = style_definitions();
+    definitions.insert("element".to_string(), json!({ "oneOf": elements }));
+    json!({
+        "$schema": SCHEMA_VERSION,
+        "title": "Layout file",
+        "$ref": "#/$defs/element",
+        "$defs": definitions,
+    })
+}
+
+fn reference(definition: &str) -> Value {
+    json!({ "$ref": format!("#/$defs/{}", definition) })
+}
+
+fn keywords(names: &[&str]) -> Value {
+    json!({ "enum": names })
+}
+
+// The schema of a `StyleRule` property, without the `"$var"` alternative.
+fn property_schema(field: &str) -> Option<Value> {
+    let number = |minimum: f32| json!({ "type": "number", "minimum": minimum });
+    Some(match field {
+        "size_full" | "italic" | "underline" | "line_through" | "text_ellipsis" | "visible" => {
+            json!({ "type": "boolean" })
+        }
+        "bg_color" | "text_color" | "border_color" => reference("color"),
+        "background" => reference("background"),
+        "font_size" => reference("font_size"),
+        "font_weight" => reference("font_weight"),
+        "font_family" => json!({ "type": "string" }),
+        "line_height" | "flex_basis" | "width" | "height" | "min_width" | "min_height"
+        | "max_width" | "max_height" | "inset" | "top" | "right" | "bottom" | "left" | "margin"
+        | "margin_top" | "margin_right" | "margin_bottom" | "margin_left" | "padding"
+        | "padding_top" | "padding_right" | "padding_bottom" | "padding_left" | "gap" | "gap_x"
+        | "gap_y" => reference("length"),
+        "text_align" => keywords(serde_names::<StyleTextAlign>()),
+        "whitespace" => keywords(serde_names::<StyleWhitespace>()),
+        "display" => keywords(serde_names::<StyleDisplay>()),
+        "justify_content" => keywords(serde_names::<StyleJustifyContent>()),
+        "align_items" | "align_self" => keywords(serde_names::<StyleAlignItems>()),
+        "flex_direction" => keywords(serde_names::<StyleFlexDirection>()),
+        "position" => keywords(serde_names::<StylePosition>()),
+        "cursor" => keywords(serde_names::<StyleCursor>()),
+        "overflow_x" | "overflow_y" => keywords(serde_names::<StyleOverflow>()),
+        "border_style" => keywords(serde_names::<StyleBorderStyle>()),
+        "flex_grow" | "flex_shrink" | "border_width" | "border_top" | "border_right"
+        | "border_bottom" | "border_left" => number(0.),
+        "opacity" => json!({ "type": "number", "minimum": 0, "maximum": 1 }),
+        "line_clamp" => json!({ "type": "integer", "minimum": 0 }),
+        "grid_cols" | "grid_rows" => json!({ "type": "integer", "minimum": 1 }),
+        "col_span" | "row_span" => reference("grid_span"),
+        "col_start" | "col_end" | "row_start" | "row_end" => json!({ "type": "integer" }),
+        "rounded" | "rounded_tl" | "rounded_tr" | "rounded_bl" | "rounded_br" => {
+            reference("radius")
+        }
+        "shadow" => reference("shadow"),
+        "transition" => reference("transition"),
+        "hover" | "active" | "focus" | "disabled" => reference("rule"),
+        "breakpoints" => {
+            let breakpoints: Map<String, Value> = serde_names::<Breakpoint>()
+                .iter()
+                .map(|b| (b.to_string(), reference("rule")))
+                .collect();
+            json!({ "type": "object", "properties": breakpoints, "additionalProperties": false })
+        }
+        _ => return None,
+    })
+}
+
+fn style_definitions() -> Map<String, Value> {
+    let mut rule_properties = Map::new();
+    for field in serde_names::<StyleRule>() {
+        let schema = property_schema(field).unwrap_or_else(|| json!({}));
+        rule_properties.insert(
+            field.to_string(),
+            json!({ "anyOf": [schema, reference("var")] }),
+        );
+    }
+    rule_properties.insert(
+        "extends".to_string(),
+        json!({
+            "description": "Classes whose properties this one starts from.",
+            "anyOf": [
+                { "type": "string" },
+                { "type": "array", "items": { "type": "string" } },
+            ],
+        }),
+    );
+
+    let theme_colors: Vec<String> = THEME_COLOR_NAMES
+        .iter()
+        .map(|name| format!("theme.{}", name))
+        .collect();
+    let box_shadow: Map<String, Value> = serde_names::<StyleBoxShadow>()
+        .iter()
+        .map(|field| {
+            let schema = match *field {
+                "color" => reference("color"),
+                _ => json!({ "type": "number" }),
+            };
+            (field.to_string(), schema)
+        })
+        .collect();
+    let transition: Map<String, Value> = serde_names::<StyleTransition>()
+        .iter()
+        .map(|field| {
+            let schema = match *field {
+                "duration_ms" => json!({ "type": "integer", "minimum": 0 }),
+                "easing" => keywords(serde_names::<StyleEasing>()),
+                _ => json!({
+                    "description": "Empty means every property that can be animated.",
+                    "type": "array",
+                    "items": keywords(serde_names::<TransitionProperty>()),
+                }),
+            };
+            (field.to_string(), schema)
+        })
+        .collect();
+    let gradient_stop = json!({
+        "anyOf": [
+            reference("color"),
+            {
+                "type": "object",
+                "properties": { "color": reference("color"), "position": { "type": "number" } },
+                "required": ["color"],
+                "additionalProperties": false,
+            },
+        ],
+    });
+
+    let definitions = json!({
+        "rule": {
+            "type": "object",
+            "properties": rule_properties,
+            "additionalProperties": false,
+        },
+        "var": {
+            "description": "A variable of `vars`.",
+            "type": "string",
+            "pattern": "^\\$",
+        },
+        "length": {
+            "description": "Pixels, or e.g. \"12px\", \"1.5rem\", \"50%\", \"1/3\", \"full\" or \"auto\".",
+            "anyOf": [
+                { "type": "number" },
+                { "type": "string", "pattern": "^\\s*(auto|full|-?[0-9.]+\\s*(px|rem|%)?|[0-9.]+\\s*/\\s*[0-9.]+)\\s*$" },
+            ],
+        },
+        "color": {
+            "description": "\"#rrggbb\", \"rgb(...)\", \"hsl(...)\", a CSS color name or \"theme.<name>\".",
+            "anyOf": [{ "enum": theme_colors }, { "type": "string" }],
+        },
+        "font_size": {
+            "description": "Pixels, \"1.25rem\" or a named size.",
+            "anyOf": [
+                { "type": "number" },
+                { "enum": ["xs", "sm", "base", "lg", "xl", "2xl", "3xl"] },
+                { "type": "string", "pattern": "^\\s*[0-9.]+\\s*(px|rem)?\\s*$" },
+            ],
+        },
+        "font_weight": {
+            "anyOf": [
+                { "type": "number", "minimum": 1, "maximum": 1000 },
+                { "enum": ["thin", "extra-light", "light", "normal", "medium", "semibold", "bold", "extra-bold", "black"] },
+            ],
+        },
+        "radius": {
+            "description": "Pixels or one of gpui's `rounded_*` presets.",
+            "anyOf": [{ "type": "number" }, keywords(serde_names::<RadiusPreset>())],
+        },
+        "grid_span": {
+            "anyOf": [{ "type": "integer", "minimum": 1 }, { "const": "full" }],
+        },
+        "shadow": {
+            "description": "One of gpui's `shadow_*` presets, a box shadow or a list of them.",
+            "oneOf": [
+                keywords(serde_names::<ShadowPreset>()),
+                { "type": "object", "properties": box_shadow, "additionalProperties": false },
+                {
+                    "type": "array",
+                    "items": { "type": "object", "properties": box_shadow, "additionalProperties": false },
+                },
+            ],
+        },
+        "background": {
+            "description": "A color, a linear gradient of two stops, or an image file.",
+            "anyOf": [
+                reference("color"),
+                {
+                    "type": "object",
+                    "properties": {
+                        "linear_gradient": {
+                            "type": "object",
+                            "properties": {
+                                "angle": { "type": "number" },
+                                "stops": { "type": "array", "items": gradient_stop, "minItems": 2, "maxItems": 2 },
+                            },
+                            "required": ["stops"],
+                            "additionalProperties": false,
+                        },
+                    },
+                    "required": ["linear_gradient"],
+                    "additionalProperties": false,
+                },
+                {
+                    "type": "object",
+                    "properties": {
+                        "image": { "type": "string", "description": "Path relative to the styles file." },
+                        "fit": keywords(serde_names::<StyleObjectFit>()),
+                    },
+                    "required": ["image"],
+                    "additionalProperties": false,
+                },
+            ],
+        },
+        "transition": {
+            "type": "object",
+            "properties": transition,
+            "required": ["duration_ms"],
+            "additionalProperties": false,
+        },
+    });
+    match definitions {
+        Value::Object(definitions) => definitions,
+        _ => unreachable!(),
+    }
+}
+
+// The field names of a struct, or the variant names of an enum, as serde reads them.
+pub fn serde_names<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    let mut names = None;
+    let _ = T::deserialize(NamesDeserializer(&mut names));
+    names.unwrap_or_default()
+}
+
+// Records the names a derived `Deserialize` asks for, then fails.
+struct NamesDeserializer<'a>(&'a mut Option<&'static [&'static str]>);
+
+impl<'de> Deserializer<'de> for NamesDeserializer<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("not a struct or an enum"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = Some(fields);
+        Err(de::Error::custom("names recorded"))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = Some(variants);
+        Err(de::Error::custom("names recorded"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        layout_schema, property_schema, serde_names, styles_schema, LAYOUT_SCHEMA, STYLES_SCHEMA,
+    };
+    use crate::my_style_data::{parse_style_map, StyleRule};
+    use serde_json::Value;
+
+    #[test]
+    fn test_schemas_are_up_to_date() {
+        for field in serde_names::<StyleRule>() {
+            assert!(
+                property_schema(field).is_some(),
+                "`StyleRule::{}` has no schema, add it to `property_schema`",
+                field
+            );
+        }
+
+        for (shipped, schema, file) in [
+            (STYLES_SCHEMA, styles_schema(), "styles.schema.json"),
+            (LAYOUT_SCHEMA, layout_schema(), "layout.schema.json"),
+        ] {
+            assert_eq!(
+                shipped.replace("\r\n", "\n"),
+                serde_json::to_string_pretty(&schema).unwrap() + "\n",
+                "schemas/{} is outdated, run `cargo run --bin style_schema`",
+                file
+            );
+        }
+
+        // Every shape serde accepts must be allowed by the schema too.
+        let styles = r##"{
+            "card": { "padding": 8, "bg_color": "#fff", "shadow": { "offset_y": 2, "blur": 4 } },
+            "list": { "shadow": [{ "blur": 4 }, { "blur": 8, "color": "#0000001a" }] },
+            "flat": { "shadow": "md", "hover": { "shadow": { "blur": 2 } } }
+        }"##;
+        parse_style_map(styles).unwrap();
+        let schema = styles_schema();
+        assert!(allows(
+            &schema,
+            &schema,
+            &serde_json::from_str(styles).unwrap()
+        ));
+        assert!(!allows(
+            &schema,
+            &schema,
+            &serde_json::json!({ "a": { "shadow": 2 } })
+        ));
+    }
+
+    #[test]
+    fn test_example_layouts() {
+        let schema = layout_schema();
+        for layout in [
+            include_str!("../examples/layout_demo/layout.pjson"),
+            include_str!("../examples/todo_list/layout.pjson"),
+        ] {
+            let json = pjson::PJsonReader::from_pjson(layout.as_bytes());
+            let value: Value = serde_json::from_slice(&json).unwrap();
+            assert!(allows(&schema, &schema, &value), "{}", value);
+        }
+
+        let button = serde_json::json!({ "type": "button", "label": "Ok" });
+        assert!(allows(&schema, &schema, &button));
+        let input = serde_json::json!({ "type": "input", "disabled": true, "bind": "state" });
+        assert!(!allows(&schema, &schema, &input));
+        assert!(!allows(
+            &schema,
+            &schema,
+            &serde_json::json!({ "type": "input" })
+        ));
+    }
+
+    // Whether `schema` allows `value` by its JSON types, keywords, required and
+    // allowed property names, following `$ref`, `anyOf` and `oneOf`. Not a full
+    // validator, but enough to catch a shape that serde accepts and the schema
+    // does not.
+    fn allows(root: &Value, schema: &Value, value: &Value) -> bool {
+        if let Some(reference) = schema["$ref"].as_str() {
+            let name = reference.trim_start_matches("#/$defs/");
+            return allows(root, &root["$defs"][name], value);
+        }
+        if let Some(any_of) = schema["anyOf"].as_array() {
+            return any_of.iter().any(|s| allows(root, s, value));
+        }
+        if let Some(one_of) = schema["oneOf"].as_array() {
+            return one_of.iter().filter(|s| allows(root, s, value)).count() == 1;
+        }
+        if let Some(names) = schema["enum"].as_array() {
+            return names.contains(value);
+        }
+        if let Some(constant) = schema.get("const") {
+            return constant == value;
+        }
+
+        let type_matches = match schema["type"].as_str() {
+            Some("object") => value.is_object(),
+            Some("array") => value.is_array(),
+            Some("string") => value.is_string(),
+            Some("number") => value.is_number(),
+            Some("integer") => value.is_i64() || value.is_u64(),
+            Some("boolean") => value.is_boolean(),
+            _ => true,
+        };
+        type_matches
+            && match value {
+                Value::Object(properties) => {
+                    let required = schema["required"].as_array().into_iter().flatten();
+                    required
+                        .filter_map(Value::as_str)
+                        .all(|name| properties.contains_key(name))
+                        && properties.iter().all(|(key, value)| {
+                            match schema["properties"].get(key) {
+                                Some(property) => allows(root, property, value),
+                                None => match schema.get("additionalProperties") {
+                                    Some(Value::Bool(allowed)) => *allowed,
+                                    Some(other) => allows(root, other, value),
+                                    None => true,
+                                },
+                            }
+                        })
+                }
+                Value::Array(items) => schema
+                    .get("items")
+                    .is_none_or(|item| items.iter().all(|value| allows(root, item, value))),
+                _ => true,
+            }
+    }
+}
@@ -0,0 +1,248 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::my_style_data::StyleRule;
+
+// The element types of a pjson layout that selectors can match.
+pub const ELEMENT_TYPES: &[&str] = &["div", "label", "button", "input"];
+
+// What a selector is matched against: an element of a pjson layout.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SelectorTarget<'a> {
+    // `"div"`, `"label"`, `"button"` or `"input"`.
+    pub element: &'a str,
+    pub id: Option<&'a str>,
+    // Space separated, as in the `class` attribute.
+    pub classes: &'a str,
+}
+
+// A rule of the top-level `selectors` object of a styles file.
+#[derive(Debug, Clone)]
+pub struct SelectorRule {
+    pub selector: Selector,
+    pub rule: StyleRule,
+}
+
+// CSS specificity: (ids, classes, element types), compared in that order.
+// Rules of equal specificity apply in the order of the styles file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Specificity(pub u16, pub u16, pub u16);
+
+impl Specificity {
+    // That of a plain class rule, as used by the `class` attribute.
+    pub const CLASS: Specificity = Specificity(0, 1, 0);
+}
+
+// `button`, `#btn_add`, `.sidebar`, `button.primary`, or a descendant
+// selector such as `.sidebar label` or `#toolbar .group button`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selector {
+    // The last one matches the element itself, the others its ancestors.
+    compounds: Vec<CompoundSelector>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct CompoundSelector {
+    element: Option<String>,
+    id: Option<String>,
+    classes: Vec<String>,
+}
+
+impl CompoundSelector {
+    fn matches(&self, target: &SelectorTarget) -> bool {
+        self.element.as_deref().is_none_or(|e| e == target.element)
+            && self.id.as_deref().is_none_or(|id| Some(id) == target.id)
+            && self
+                .classes
+                .iter()
+                .all(|class| target.classes.split(' ').any(|c| c == class))
+    }
+}
+
+impl Selector {
+    // `path` is the element last, preceded by its ancestors from the root.
+    pub fn matches(&self, path: &[SelectorTarget]) -> bool {
+        let Some((target, ancestors)) = path.split_last() else {
+            return false;
+        };
+        let Some((last, rest)) = self.compounds.split_last() else {
+            return false;
+        };
+        if !last.matches(target) {
+            return false;
+        }
+
+        // Descendant combinators only, so matching the nearest ancestor first is enough.
+        let mut ancestors = ancestors.iter().rev();
+        rest.iter()
+            .rev()
+            .all(|compound| ancestors.any(|ancestor| compound.matches(ancestor)))
+    }
+
+    // The classes of all compounds, e.g. `sidebar` and `primary` of
+    // `.sidebar button.primary`.
+    pub fn classes(&self) -> impl Iterator<Item = &str> {
+        self.compounds
+            .iter()
+            .flat_map(|compound| compound.classes.iter().map(String::as_str))
+    }
+
+    pub fn specificity(&self) -> Specificity {
+        self.compounds.iter().fold(
+            Specificity::default(),
+            |Specificity(ids, classes, types), c| {
+                Specificity(
+                    ids + c.id.is_some() as u16,
+                    classes + c.classes.len() as u16,
+                    types + c.element.is_some() as u16,
+                )
+            },
+        )
+    }
+}
+
+impl FromStr for Selector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let compounds = s
+            .split_whitespace()
+            .map(parse_compound)
+            .collect::<Result<Vec<_>, _>>()?;
+        if compounds.is_empty() {
+            return Err("empty selector".to_string());
+        }
+        Ok(Selector { compounds })
+    }
+}
+
+// Written back in the syntax it is parsed from, which is also that of CSS.
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, compound) in self.compounds.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            f.write_str(compound.element.as_deref().unwrap_or_default())?;
+            if let Some(id) = &compound.id {
+                write!(f, "#{}", id)?;
+            }
+            for class in &compound.classes {
+                write!(f, ".{}", class)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_compound(s: &str) -> Result<CompoundSelector, String> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+    let mut compound = CompoundSelector::default();
+    let mut rest = s;
+
+    let element_len = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+    if element_len > 0 {
+        let element = match &rest[..element_len] {
+            // As in the `type` attribute of a layout element.
+            "text_input" => "input",
+            element => element,
+        };
+        if !ELEMENT_TYPES.contains(&element) {
+            return Err(format!(
+                "unknown element type `{}`, expected one of {}",
+                element,
+                ELEMENT_TYPES.join(", ")
+            ));
+        }
+        compound.element = Some(element.to_string());
+        rest = &rest[element_len..];
+    }
+
+    while let Some(prefix) = rest.chars().next() {
+        if prefix != '.' && prefix != '#' {
+            return Err(format!("unexpected `{}` in `{}`", prefix, s));
+        }
+        let body = &rest[1..];
+        let name_len = body.find(|c| !is_name_char(c)).unwrap_or(body.len());
+        let name = body[..name_len].to_string();
+        if name.is_empty() {
+            return Err(format!("missing name after `{}` in `{}`", prefix, s));
+        }
+        if prefix == '.' {
+            compound.classes.push(name);
+        } else if compound.id.is_none() {
+            compound.id = Some(name);
+        } else {
+            return Err(format!("more than one id in `{}`", s));
+        }
+        rest = &body[name_len..];
+    }
+
+    Ok(compound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Selector, SelectorTarget, Specificity};
+
+    fn parse(s: &str) -> Result<Selector, String> {
+        s.parse()
+    }
+
+    fn target<'a>(element: &'a str, id: Option<&'a str>, classes: &'a str) -> SelectorTarget<'a> {
+        SelectorTarget {
+            element,
+            id,
+            classes,
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |s: &str| parse(s).unwrap_err();
+        assert!(error("span").contains("unknown element type `span`"));
+        assert!(error("div>label").contains("unexpected `>`"));
+        assert!(error("button.").contains("missing name after `.`"));
+        assert!(error("#a#b").contains("more than one id"));
+        assert_eq!(error("  "), "empty selector");
+
+        assert_eq!(parse("text_input.wide").unwrap().to_string(), "input.wide");
+        assert_eq!(
+            parse("#toolbar  .group button").unwrap().to_string(),
+            "#toolbar .group button"
+        );
+    }
+
+    #[test]
+    fn test_specificity() {
+        let specificity = |s: &str| parse(s).unwrap().specificity();
+        assert_eq!(specificity("#toolbar .group button"), Specificity(1, 1, 1));
+        assert_eq!(specificity(".a"), Specificity::CLASS);
+        assert!(specificity("#a") > specificity(".a.b.c"));
+        assert!(specificity(".a") > specificity("div label button"));
+        assert!(specificity("button.a") > specificity(".a"));
+        assert!(specificity(".sidebar label") > specificity(".sidebar"));
+    }
+
+    #[test]
+    fn test_matches() {
+        let sidebar = target("div", None, "sidebar dark");
+        let group = target("div", Some("group"), "");
+        let label = target("label", None, "title");
+        let matches = |s: &str, path: &[SelectorTarget]| parse(s).unwrap().matches(path);
+
+        // Ancestors need not be the parent.
+        assert!(matches(".sidebar label", &[sidebar, group, label]));
+        assert!(matches(
+            "div.dark #group label.title",
+            &[sidebar, group, label]
+        ));
+        assert!(!matches("#group .sidebar label", &[sidebar, group, label]));
+        // The last compound matches the element itself.
+        assert!(!matches(".sidebar", &[sidebar, group, label]));
+        assert!(!matches("label label", &[label]));
+        // Classes are matched whole.
+        assert!(!matches(".side", &[sidebar]));
+        assert!(!matches("div", &[]));
+    }
+}
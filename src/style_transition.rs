@@ -0,0 +1,523 @@
+use gpui::{
+    AnyElement, App, Bounds, Element, ElementId, GlobalElementId, InspectorElementId, IntoElement,
+    LayoutId, MouseButton, Pixels, Rgba, StatefulInteractiveElement, Styled, Window,
+};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::panic::Location;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::my_style_data::{apply_rule_properties, StyleColor, StyleLength, StyleRule};
+
+// `transition: { duration_ms: 200, easing: "ease-out", properties: ["bg_color", "height"] }`
+//
+// Changes of the listed properties of an element are animated, whether they come
+// from a reload, a theme switch, a breakpoint, a change of its classes or its
+// `hover` and `active` sub-rules, see `Transitions::track_pointer`. `focus`
+// sub-rules are applied by gpui, which only reports focus to the element's own
+// focus handle, and switch instantly.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct StyleTransition {
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub easing: StyleEasing,
+    // Empty means every property that can be animated.
+    #[serde(default)]
+    pub properties: Vec<TransitionProperty>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StyleEasing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    #[default]
+    EaseInOut,
+}
+
+impl StyleEasing {
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            StyleEasing::Linear => t,
+            StyleEasing::EaseIn => gpui::quadratic(t),
+            StyleEasing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            StyleEasing::EaseInOut => gpui::ease_in_out(t),
+        }
+    }
+}
+
+// The properties that can be animated. Lengths only animate between values of the
+// same unit; anything else, like `"auto"` or a missing value, switches instantly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionProperty {
+    All,
+    BgColor,
+    TextColor,
+    BorderColor,
+    Opacity,
+    Width,
+    Height,
+    // All four sides, including the `margin` and `padding` shorthands.
+    Margin,
+    Padding,
+    // `gap`, `gap_x` and `gap_y`.
+    Gap,
+}
+
+impl StyleTransition {
+    fn animates(&self, property: TransitionProperty) -> bool {
+        self.properties.is_empty()
+            || self
+                .properties
+                .iter()
+                .any(|p| *p == property || *p == TransitionProperty::All)
+    }
+
+    fn duration(&self) -> Duration {
+        // A zero duration would make gpui's animation progress NaN.
+        Duration::from_millis(self.duration_ms.max(1))
+    }
+}
+
+// The animated properties of `from` and `to` at `delta` (0.0 to 1.0, already eased),
+// to be applied on top of the style of `to`.
+pub fn interpolate(
+    from: &StyleRule,
+    to: &StyleRule,
+    delta: f32,
+    transition: &StyleTransition,
+) -> StyleRule {
+    use TransitionProperty::*;
+
+    let mut rule = StyleRule::default();
+    if transition.animates(BgColor) {
+        rule.bg_color = lerp_color(&from.bg_color, &to.bg_color, delta);
+    }
+    if transition.animates(TextColor) {
+        rule.text_color = lerp_color(&from.text_color, &to.text_color, delta);
+    }
+    if transition.animates(BorderColor) {
+        rule.border_color = lerp_color(&from.border_color, &to.border_color, delta);
+    }
+    if transition.animates(Opacity) && (from.opacity.is_some() || to.opacity.is_some()) {
+        let from = from.opacity.unwrap_or(1.0);
+        let to = to.opacity.unwrap_or(1.0);
+        rule.opacity = Some(lerp(from, to, delta));
+    }
+    if transition.animates(Width) {
+        rule.width = lerp_length(from.width, to.width, delta);
+    }
+    if transition.animates(Height) {
+        rule.height = lerp_length(from.height, to.height, delta);
+    }
+    if transition.animates(Margin) {
+        let side = |rule: &StyleRule, side: Option<StyleLength>| side.or(rule.margin);
+        rule.margin_top = lerp_length(side(from, from.margin_top), side(to, to.margin_top), delta);
+        rule.margin_right = lerp_length(
+            side(from, from.margin_right),
+            side(to, to.margin_right),
+            delta,
+        );
+        rule.margin_bottom = lerp_length(
+            side(from, from.margin_bottom),
+            side(to, to.margin_bottom),
+            delta,
+        );
+        rule.margin_left = lerp_length(
+            side(from, from.margin_left),
+            side(to, to.margin_left),
+            delta,
+        );
+    }
+    if transition.animates(Padding) {
+        let side = |rule: &StyleRule, side: Option<StyleLength>| side.or(rule.padding);
+        rule.padding_top = lerp_length(
+            side(from, from.padding_top),
+            side(to, to.padding_top),
+            delta,
+        );
+        rule.padding_right = lerp_length(
+            side(from, from.padding_right),
+            side(to, to.padding_right),
+            delta,
+        );
+        rule.padding_bottom = lerp_length(
+            side(from, from.padding_bottom),
+            side(to, to.padding_bottom),
+            delta,
+        );
+        rule.padding_left = lerp_length(
+            side(from, from.padding_left),
+            side(to, to.padding_left),
+            delta,
+        );
+    }
+    if transition.animates(Gap) {
+        rule.gap_x = lerp_length(from.gap_x.or(from.gap), to.gap_x.or(to.gap), delta);
+        rule.gap_y = lerp_length(from.gap_y.or(from.gap), to.gap_y.or(to.gap), delta);
+    }
+    rule
+}
+
+fn lerp(from: f32, to: f32, delta: f32) -> f32 {
+    from + (to - from) * delta
+}
+
+fn lerp_color(
+    from: &Option<StyleColor>,
+    to: &Option<StyleColor>,
+    delta: f32,
+) -> Option<StyleColor> {
+    match (
+        from.as_ref().and_then(StyleColor::value),
+        to.as_ref().and_then(StyleColor::value),
+    ) {
+        (Some(from), Some(to)) => Some(StyleColor::Rgba(Rgba {
+            r: lerp(from.r, to.r, delta),
+            g: lerp(from.g, to.g, delta),
+            b: lerp(from.b, to.b, delta),
+            a: lerp(from.a, to.a, delta),
+        })),
+        _ => to.clone(),
+    }
+}
+
+fn lerp_length(
+    from: Option<StyleLength>,
+    to: Option<StyleLength>,
+    delta: f32,
+) -> Option<StyleLength> {
+    match (from, to) {
+        (Some(StyleLength::Px(from)), Some(StyleLength::Px(to))) => {
+            Some(StyleLength::Px(lerp(from, to, delta)))
+        }
+        (Some(StyleLength::Rems(from)), Some(StyleLength::Rems(to))) => {
+            Some(StyleLength::Rems(lerp(from, to, delta)))
+        }
+        (Some(from), Some(to)) => match (from.relative(), to.relative()) {
+            (Some(from), Some(to)) => Some(StyleLength::Relative(lerp(from, to, delta))),
+            _ => Some(to),
+        },
+        (_, to) => to,
+    }
+}
+
+#[derive(Debug)]
+struct TransitionState {
+    from: StyleRule,
+    to: StyleRule,
+    started: Instant,
+}
+
+impl TransitionState {
+    fn progress(&self, transition: &StyleTransition, now: Instant) -> f32 {
+        let t =
+            now.duration_since(self.started).as_secs_f32() / transition.duration().as_secs_f32();
+        transition.easing.apply(t.min(1.0))
+    }
+}
+
+// Whether an element is hovered and pressed, see `Transitions::track_pointer`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct PointerState {
+    hovered: bool,
+    pressed: bool,
+}
+
+// The last resolved style of every element with a transition, keyed by element id.
+#[derive(Debug, Default)]
+pub struct Transitions {
+    states: RefCell<HashMap<String, TransitionState>>,
+    // Set by the listeners of `track_pointer`.
+    pointer: Arc<Mutex<HashMap<String, PointerState>>>,
+}
+
+impl Transitions {
+    // Keep the running transitions of a previous `MyStyleData`, so that a reload
+    // animates from the old styles.
+    pub fn take_from(&self, other: &Transitions) {
+        self.states.replace(other.states.take());
+        *self.pointer.lock().unwrap() = other.pointer.lock().unwrap().clone();
+    }
+
+    // Follow whether element `id` is hovered and pressed with the left button,
+    // redrawing the window when that changes, for `pointer_rules`.
+    pub fn track_pointer<E: StatefulInteractiveElement>(&self, id: &str, el: E) -> E {
+        let listener = |change: fn(&mut PointerState, bool)| {
+            let pointer = self.pointer.clone();
+            let id = id.to_string();
+            move |on: bool, window: &mut Window| {
+                let mut pointer = pointer.lock().unwrap();
+                let state = pointer.entry(id.clone()).or_default();
+                let old = *state;
+                change(state, on);
+                if *state != old {
+                    window.refresh();
+                }
+            }
+        };
+        let hover = listener(|state, on| state.hovered = on);
+        let press = listener(|state, on| state.pressed = on);
+        let release = press.clone();
+        let release_out = press.clone();
+
+        el.on_hover(move |hovered, window, _| hover(*hovered, window))
+            .on_mouse_down(MouseButton::Left, move |_, window, _| press(true, window))
+            .on_mouse_up(MouseButton::Left, move |_, window, _| {
+                release(false, window)
+            })
+            .on_mouse_up_out(MouseButton::Left, move |_, window, _| {
+                release_out(false, window)
+            })
+    }
+
+    // The `hover` and `active` sub-rules of `rule` matching the state of element
+    // `id`, none when it is `disabled`.
+    pub fn pointer_rules<'a>(
+        &self,
+        id: &str,
+        rule: &'a StyleRule,
+        disabled: bool,
+    ) -> Vec<&'a StyleRule> {
+        let state = self.pointer.lock().unwrap().get(id).copied();
+        let state = state.unwrap_or_default();
+        [(state.hovered, &rule.hover), (state.pressed, &rule.active)]
+            .into_iter()
+            .filter(|(on, _)| *on && !disabled)
+            .filter_map(|(_, rule)| rule.as_deref())
+            .collect()
+    }
+
+    // Record `target` as the style of element `id`, returning the transition to
+    // run if it changed while a previous style is known.
+    pub fn update(&self, id: &str, target: &StyleRule) -> Option<RunningTransition> {
+        let mut states = self.states.borrow_mut();
+        let Some(transition) = &target.transition else {
+            states.remove(id);
+            return None;
+        };
+
+        let now = Instant::now();
+        let state = match states.get_mut(id) {
+            Some(state) => state,
+            None => {
+                states.insert(
+                    id.to_string(),
+                    TransitionState {
+                        from: target.clone(),
+                        to: target.clone(),
+                        started: now,
+                    },
+                );
+                return None;
+            }
+        };
+
+        if state.to != *target {
+            // Start from wherever a running transition got to.
+            let all = StyleTransition {
+                properties: Vec::new(),
+                ..transition.clone()
+            };
+            let mut from = state.to.clone();
+            from.merge(&interpolate(
+                &state.from,
+                &state.to,
+                state.progress(&all, now),
+                &all,
+            ));
+            state.from = from;
+            state.to = target.clone();
+            state.started = now;
+        } else if state.from == state.to
+            || now.duration_since(state.started) >= transition.duration()
+        {
+            return None;
+        }
+
+        Some(RunningTransition {
+            from: state.from.clone(),
+            to: state.to.clone(),
+            transition: transition.clone(),
+            delta: state.progress(transition, now),
+        })
+    }
+}
+
+pub struct RunningTransition {
+    from: StyleRule,
+    to: StyleRule,
+    transition: StyleTransition,
+    // How far it got when it was looked up, already eased.
+    delta: f32,
+}
+
+impl RunningTransition {
+    // Apply the transitioned properties as far as they got to `el`, which already
+    // has the final style, and render it again on the next frame. Unlike gpui's
+    // `with_animation`, this keeps the element's place in the element tree, so
+    // that the state of its listeners, like whether it is hovered, is kept.
+    pub fn animate<E>(self, el: E) -> AnyElement
+    where
+        E: Styled + IntoElement + 'static,
+    {
+        let rule = interpolate(&self.from, &self.to, self.delta, &self.transition);
+        NextFrame(apply_rule_properties(el, &rule).into_any_element()).into_any_element()
+    }
+}
+
+// An element that asks for another frame while it is laid out.
+struct NextFrame(AnyElement);
+
+impl IntoElement for NextFrame {
+    type Element = Self;
+
+    fn into_element(self) -> Self {
+        self
+    }
+}
+
+impl Element for NextFrame {
+    type RequestLayoutState = ();
+    type PrepaintState = ();
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn source_location(&self) -> Option<&'static Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, ()) {
+        window.request_animation_frame();
+        (self.0.request_layout(window, cx), ())
+    }
+
+    fn prepaint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        _bounds: Bounds<Pixels>,
+        _request_layout: &mut (),
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        self.0.prepaint(window, cx);
+    }
+
+    fn paint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        _bounds: Bounds<Pixels>,
+        _request_layout: &mut (),
+        _prepaint: &mut (),
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        self.0.paint(window, cx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{interpolate, PointerState, StyleTransition, Transitions};
+    use crate::my_style_data::{parse_style_map, StyleColor, StyleLength, StyleRule};
+
+    fn rule(json: &str) -> StyleRule {
+        parse_style_map(&format!(r#"{{ "a": {} }}"#, json)).unwrap()["a"].clone()
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let from = rule(r#"{ "height": 100, "bg_color": "black", "margin": 0, "width": "auto" }"#);
+        let to = rule(
+            r#"{ "height": 300, "bg_color": "white", "margin_top": 10, "width": 20, "opacity": 0.5,
+                 "transition": { "duration_ms": 200, "properties": ["height", "margin", "width"] } }"#,
+        );
+        let transition = to.transition.as_ref().unwrap();
+        let halfway = interpolate(&from, &to, 0.5, transition);
+        assert_eq!(halfway.height, Some(StyleLength::Px(200.0)));
+        assert_eq!(halfway.margin_top, Some(StyleLength::Px(5.0)));
+        assert_eq!(halfway.margin_left, None);
+        // Not listed.
+        assert_eq!(halfway.bg_color, None);
+        assert_eq!(halfway.opacity, None);
+        // No unit to interpolate from.
+        assert_eq!(halfway.width, Some(StyleLength::Px(20.0)));
+
+        let all = StyleTransition {
+            properties: Vec::new(),
+            ..transition.clone()
+        };
+        let halfway = interpolate(&from, &to, 0.5, &all);
+        assert_eq!(halfway.opacity, Some(0.75));
+        let Some(StyleColor::Rgba(bg)) = halfway.bg_color else {
+            panic!("{:?}", halfway.bg_color);
+        };
+        assert_eq!((bg.r, bg.g, bg.b, bg.a), (0.5, 0.5, 0.5, 1.0));
+    }
+
+    #[test]
+    fn test_update() {
+        let transitions = Transitions::default();
+        let short = rule(r#"{ "height": 10, "transition": { "duration_ms": 60000 } }"#);
+        let tall = rule(r#"{ "height": 30, "transition": { "duration_ms": 60000 } }"#);
+
+        // Nothing to animate from yet, nor while the style stays the same.
+        assert!(transitions.update("p", &short).is_none());
+        assert!(transitions.update("p", &short).is_none());
+        // Runs until its duration is over.
+        assert!(transitions.update("p", &tall).is_some());
+        assert!(transitions.update("p", &tall).is_some());
+        // Switching back starts from where the running one got to, barely
+        // past its start, instead of from its end.
+        let back = transitions.update("p", &short).unwrap();
+        assert_eq!(back.to, short);
+        assert!(matches!(back.from.height, Some(StyleLength::Px(height)) if height < 11.0));
+
+        // Without a transition the element is forgotten.
+        assert!(transitions
+            .update("p", &rule(r#"{ "height": 30 }"#))
+            .is_none());
+        assert!(transitions.update("p", &tall).is_none());
+    }
+
+    #[test]
+    fn test_pointer_rules() {
+        let transitions = Transitions::default();
+        let button = rule(r#"{ "hover": { "opacity": 0.8 }, "active": { "opacity": 0.5 } }"#);
+        assert!(transitions.pointer_rules("b", &button, false).is_empty());
+
+        let state = PointerState {
+            hovered: true,
+            pressed: true,
+        };
+        transitions
+            .pointer
+            .lock()
+            .unwrap()
+            .insert("b".into(), state);
+        let rules = transitions.pointer_rules("b", &button, false);
+        let opacities: Vec<_> = rules.iter().map(|rule| rule.opacity).collect();
+        assert_eq!(opacities, [Some(0.8), Some(0.5)]);
+        assert!(transitions.pointer_rules("b", &button, true).is_empty());
+
+        // Carried over a reload.
+        let reloaded = Transitions::default();
+        reloaded.take_from(&transitions);
+        assert_eq!(reloaded.pointer_rules("b", &button, false).len(), 2);
+    }
+}
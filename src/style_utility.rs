@@ -0,0 +1,348 @@
+use serde_json::{json, Map, Value};
+
+use crate::css_color::parse_css_color;
+use crate::my_style_data::StyleRule;
+
+// Parse a Tailwind-style utility class, named like gpui's `Styled` methods:
+// `flex`, `flex-col`, `p-4`, `gap-x-2`, `w-1/2`, `bg-[#fff]`, `text-sm`,
+// `rounded-lg`, `shadow-md`, `opacity-50`, ... Spacing numbers use gpui's scale,
+// where 1 is 0.25rem, and `[...]` holds any value a styles file accepts, e.g.
+// `w-[120px]` or `bg-[theme.primary]`. A `hover:`, `active:`, `focus:` or
+// `disabled:` prefix puts the rule in that sub-rule, and a breakpoint prefix
+// (`sm:`, `md:`, `lg:`, `xl:`, `2xl:`) in `breakpoints`.
+//
+// Returns `None` if `token` is not a utility class, and an error if it is one
+// with an invalid `[...]` value. Without `[...]`, a name whose value does not
+// fit the property, such as `text-muted` or `shadow-card`, is an ordinary class.
+pub fn parse_utility_class(token: &str) -> Option<Result<StyleRule, String>> {
+    // Arbitrary values may contain `:`, e.g. `bg-[hsl(0deg 0% 0% / 50%)]`.
+    let plain_len = token.find('[').unwrap_or(token.len());
+    let (variants, utility) = match token[..plain_len].rfind(':') {
+        Some(i) => (&token[..i], &token[i + 1..]),
+        None => ("", token),
+    };
+
+    let mut value = Value::Object(utility_properties(utility)?);
+    for variant in variants.split(':').rev().filter(|v| !v.is_empty()) {
+        value = match variant {
+            "hover" | "active" | "focus" | "disabled" => json!({ variant: value }),
+            "sm" | "md" | "lg" | "xl" | "2xl" => json!({ "breakpoints": { variant: value } }),
+            _ => return None,
+        };
+    }
+
+    match serde_json::from_value(value) {
+        Ok(rule) => Some(Ok(rule)),
+        Err(_) if plain_len == token.len() => None,
+        Err(e) => Some(Err(format!("invalid utility class `{}`: {}", token, e))),
+    }
+}
+
+// Prefixes of length utilities and the properties they set, longest first
+// where one is a prefix of another.
+const LENGTH_UTILITIES: &[(&str, &[&str])] = &[
+    ("min-w-", &["min_width"]),
+    ("min-h-", &["min_height"]),
+    ("max-w-", &["max_width"]),
+    ("max-h-", &["max_height"]),
+    ("size-", &["width", "height"]),
+    ("w-", &["width"]),
+    ("h-", &["height"]),
+    ("basis-", &["flex_basis"]),
+    ("gap-x-", &["gap_x"]),
+    ("gap-y-", &["gap_y"]),
+    ("gap-", &["gap"]),
+    ("inset-", &["inset"]),
+    ("top-", &["top"]),
+    ("right-", &["right"]),
+    ("bottom-", &["bottom"]),
+    ("left-", &["left"]),
+    ("p-", &["padding"]),
+    ("px-", &["padding_left", "padding_right"]),
+    ("py-", &["padding_top", "padding_bottom"]),
+    ("pt-", &["padding_top"]),
+    ("pr-", &["padding_right"]),
+    ("pb-", &["padding_bottom"]),
+    ("pl-", &["padding_left"]),
+    ("m-", &["margin"]),
+    ("mx-", &["margin_left", "margin_right"]),
+    ("my-", &["margin_top", "margin_bottom"]),
+    ("mt-", &["margin_top"]),
+    ("mr-", &["margin_right"]),
+    ("mb-", &["margin_bottom"]),
+    ("ml-", &["margin_left"]),
+    ("leading-", &["line_height"]),
+];
+
+// Prefixes of utilities taking a value of the property as is.
+const VALUE_UTILITIES: &[(&str, &str)] = &[
+    ("items-", "align_items"),
+    ("self-", "align_self"),
+    ("font-", "font_weight"),
+    ("cursor-", "cursor"),
+    ("overflow-x-", "overflow_x"),
+    ("overflow-y-", "overflow_y"),
+    ("grid-cols-", "grid_cols"),
+    ("grid-rows-", "grid_rows"),
+    ("col-span-", "col_span"),
+    ("row-span-", "row_span"),
+    ("col-start-", "col_start"),
+    ("col-end-", "col_end"),
+    ("row-start-", "row_start"),
+    ("row-end-", "row_end"),
+    ("rounded-tl-", "rounded_tl"),
+    ("rounded-tr-", "rounded_tr"),
+    ("rounded-bl-", "rounded_bl"),
+    ("rounded-br-", "rounded_br"),
+    ("rounded-", "rounded"),
+    ("shadow-", "shadow"),
+    ("border-t-", "border_top"),
+    ("border-r-", "border_right"),
+    ("border-b-", "border_bottom"),
+    ("border-l-", "border_left"),
+];
+
+fn utility_properties(utility: &str) -> Option<Map<String, Value>> {
+    let props = |props: &[(&str, Value)]| {
+        Some(
+            props
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
+        )
+    };
+
+    match utility {
+        "flex" | "grid" | "block" => return props(&[("display", json!(utility))]),
+        "hidden" => return props(&[("display", json!("none"))]),
+        "flex-row" => return props(&[("flex_direction", json!("row"))]),
+        "flex-col" => return props(&[("flex_direction", json!("column"))]),
+        "flex-row-reverse" => return props(&[("flex_direction", json!("row-reverse"))]),
+        "flex-col-reverse" => return props(&[("flex_direction", json!("column-reverse"))]),
+        "flex-1" => {
+            return props(&[
+                ("flex_grow", json!(1)),
+                ("flex_shrink", json!(1)),
+                ("flex_basis", json!("0%")),
+            ])
+        }
+        "flex-auto" => {
+            return props(&[
+                ("flex_grow", json!(1)),
+                ("flex_shrink", json!(1)),
+                ("flex_basis", json!("auto")),
+            ])
+        }
+        "flex-none" => return props(&[("flex_grow", json!(0)), ("flex_shrink", json!(0))]),
+        "grow" | "flex-grow" => return props(&[("flex_grow", json!(1))]),
+        "grow-0" | "flex-grow-0" => return props(&[("flex_grow", json!(0))]),
+        "shrink" | "flex-shrink" => return props(&[("flex_shrink", json!(1))]),
+        "shrink-0" | "flex-shrink-0" => return props(&[("flex_shrink", json!(0))]),
+        "size-full" => return props(&[("size_full", json!(true))]),
+        "relative" | "absolute" => return props(&[("position", json!(utility))]),
+        "visible" => return props(&[("visible", json!(true))]),
+        "invisible" => return props(&[("visible", json!(false))]),
+        "italic" | "underline" | "line-through" => {
+            return props(&[(utility.replace('-', "_").as_str(), json!(true))])
+        }
+        "truncate" => {
+            return props(&[
+                ("text_ellipsis", json!(true)),
+                ("whitespace", json!("nowrap")),
+                ("overflow_x", json!("hidden")),
+            ])
+        }
+        "whitespace-nowrap" => return props(&[("whitespace", json!("nowrap"))]),
+        "whitespace-normal" => return props(&[("whitespace", json!("normal"))]),
+        "border" => return props(&[("border_width", json!(1))]),
+        "border-solid" | "border-dashed" => {
+            return props(&[("border_style", json!(&utility["border-".len()..]))])
+        }
+        "rounded" => return props(&[("rounded", json!("sm"))]),
+        "shadow" => return props(&[("shadow", json!("sm"))]),
+        _ => {}
+    }
+
+    if let Some(v) = utility.strip_prefix("justify-") {
+        let v = match v {
+            "between" | "around" | "evenly" => format!("space-{}", v),
+            _ => v.to_string(),
+        };
+        return props(&[("justify_content", json!(v))]);
+    }
+    if let Some(v) = utility
+        .strip_prefix("overflow-")
+        .filter(|v| !v.starts_with("x-") && !v.starts_with("y-"))
+    {
+        return props(&[("overflow_x", json!(v)), ("overflow_y", json!(v))]);
+    }
+    if let Some(v) = utility.strip_prefix("opacity-") {
+        let opacity = match arbitrary(v) {
+            Some(v) => v,
+            None => json!(v.parse::<f32>().ok()? / 100.0),
+        };
+        return props(&[("opacity", opacity)]);
+    }
+    if let Some(v) = utility.strip_prefix("text-") {
+        return match v {
+            "left" | "center" | "right" => props(&[("text_align", json!(v))]),
+            _ => match color(v) {
+                Some(c) => props(&[("text_color", c)]),
+                None => props(&[("font_size", arbitrary(v).unwrap_or(json!(v)))]),
+            },
+        };
+    }
+    if let Some(v) = utility.strip_prefix("bg-") {
+        // An invalid `[...]` color is reported when the rule is parsed.
+        return props(&[("bg_color", color(v).or_else(|| arbitrary(v))?)]);
+    }
+    if let Some(v) = utility.strip_prefix("border-") {
+        if let Some(c) = color(v) {
+            return props(&[("border_color", c)]);
+        }
+        match arbitrary(v) {
+            Some(width) if width.is_number() => return props(&[("border_width", width)]),
+            Some(c) => return props(&[("border_color", c)]),
+            None => {}
+        }
+        if let Ok(width) = v.parse::<f32>() {
+            return props(&[("border_width", json!(width))]);
+        }
+    }
+
+    for (prefix, properties) in LENGTH_UTILITIES {
+        if let Some(v) = utility.strip_prefix(prefix) {
+            let length = spacing(v)?;
+            return Some(
+                properties
+                    .iter()
+                    .map(|p| (p.to_string(), length.clone()))
+                    .collect(),
+            );
+        }
+    }
+    for (prefix, property) in VALUE_UTILITIES {
+        if let Some(v) = utility.strip_prefix(prefix) {
+            let value = arbitrary(v).unwrap_or_else(|| match v.parse::<f64>() {
+                Ok(n) => json!(n),
+                Err(_) => json!(v),
+            });
+            return props(&[(property, value)]);
+        }
+    }
+
+    None
+}
+
+// The value of `[...]`, a number if it is one; pixels such as `12px` are numbers too.
+fn arbitrary(v: &str) -> Option<Value> {
+    let v = v.strip_prefix('[')?.strip_suffix(']')?;
+    Some(match v.strip_suffix("px").unwrap_or(v).parse::<f64>() {
+        Ok(n) => json!(n),
+        Err(_) => json!(v),
+    })
+}
+
+// gpui's spacing scale: `4` is 1rem, `px` is 1px, fractions and `full`/`auto` as is.
+fn spacing(v: &str) -> Option<Value> {
+    if let Some(v) = arbitrary(v) {
+        return Some(v);
+    }
+    match v {
+        "px" => Some(json!(1)),
+        "full" | "auto" => Some(json!(v)),
+        _ if v.contains('/') => Some(json!(v)),
+        _ => Some(json!(format!("{}rem", v.parse::<f32>().ok()? / 4.0))),
+    }
+}
+
+// `[#fff]`, `[theme.primary]` or a CSS color name such as `white`.
+fn color(v: &str) -> Option<Value> {
+    match v.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+        Some(c) if c.starts_with("theme.") || parse_css_color(c).is_ok() => Some(json!(c)),
+        Some(_) => None,
+        None => parse_css_color(v).ok().map(|_| json!(v)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_utility_class;
+    use crate::my_style_data::StyleRule;
+    use serde_json::{json, Value};
+
+    // The rule of a utility class.
+    fn rule(token: &str) -> StyleRule {
+        match parse_utility_class(token) {
+            Some(Ok(rule)) => rule,
+            other => panic!("`{}`: {:?}", token, other),
+        }
+    }
+
+    // The rule written as `json` in a styles file.
+    fn style(json: Value) -> StyleRule {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_text_color_or_font_size() {
+        assert_eq!(rule("text-red"), style(json!({ "text_color": "red" })));
+        assert_eq!(
+            rule("text-[#3366ff]"),
+            style(json!({ "text_color": "#3366ff" }))
+        );
+        assert_eq!(
+            rule("text-[theme.muted_foreground]"),
+            style(json!({ "text_color": "theme.muted_foreground" }))
+        );
+        assert_eq!(rule("text-[20px]"), style(json!({ "font_size": 20.0 })));
+        assert!(rule("text-sm").font_size.is_some());
+        assert_eq!(
+            rule("text-center"),
+            style(json!({ "text_align": "center" }))
+        );
+        assert!(matches!(parse_utility_class("text-[huge]"), Some(Err(_))));
+    }
+
+    #[test]
+    fn test_border_width_or_color() {
+        assert_eq!(rule("border-[3px]"), style(json!({ "border_width": 3.0 })));
+        assert_eq!(
+            rule("border-[red]"),
+            style(json!({ "border_color": "red" }))
+        );
+        assert_eq!(rule("border-2"), style(json!({ "border_width": 2.0 })));
+        assert_eq!(
+            rule("border-white"),
+            style(json!({ "border_color": "white" }))
+        );
+        assert!(matches!(
+            parse_utility_class("border-[thick]"),
+            Some(Err(_))
+        ));
+    }
+
+    #[test]
+    fn test_variants() {
+        // The `:` inside `[...]` is not a variant separator.
+        assert_eq!(
+            rule("hover:bg-[rgb(0 0 0 / 50%)]"),
+            style(json!({ "hover": { "bg_color": "rgb(0 0 0 / 50%)" } }))
+        );
+        assert_eq!(
+            rule("md:hover:p-2"),
+            style(json!({ "breakpoints": { "md": { "hover": { "padding": "0.5rem" } } } }))
+        );
+        assert!(parse_utility_class("group:p-2").is_none());
+    }
+
+    #[test]
+    fn test_ordinary_classes() {
+        // Names whose value does not fit the property are not utilities.
+        for class in ["text-muted", "shadow-card", "p-header", "w-a/b", "card"] {
+            assert!(parse_utility_class(class).is_none(), "{}", class);
+        }
+        assert_eq!(rule("w-1/3"), style(json!({ "width": "1/3" })));
+        assert_eq!(rule("size-full"), style(json!({ "size_full": true })));
+    }
+}